@@ -1,3 +1,4 @@
+use collections::BTreeSet;
 use gstd::exec;
 use gstd::msg;
 use sails_rs::prelude::*;
@@ -6,6 +7,9 @@ use crate::storage::*;
 use crate::types::*;
 use crate::utils::*;
 
+/// Proposed owners can accept the ownership for a week by default.
+const DEFAULT_OWNER_PROPOSAL_TTL: u64 = 7 * 24 * 3_600_000;
+
 #[derive(Default)]
 pub struct AcelonOracleService();
 
@@ -15,6 +19,7 @@ impl AcelonOracleService {
             STORAGE = Some(Storage::default());
         }
         Storage::owners().insert(owner, ());
+        Storage::config().owner_proposal_ttl = DEFAULT_OWNER_PROPOSAL_TTL;
         Self()
     }
 
@@ -29,10 +34,10 @@ impl AcelonOracleService {
 
     fn ensure_proposed_owner() -> Result<(), AcelonError> {
         let proposed_owners = Storage::proposed_owners();
-        if proposed_owners.contains_key(&msg::source()) {
-            Ok(())
-        } else {
-            Err(AcelonError::NotProposedOwner)
+        match proposed_owners.get(&msg::source()) {
+            Some(expires_at) if *expires_at >= exec::block_timestamp() => Ok(()),
+            Some(_) => Err(AcelonError::OwnerProposalExpired),
+            None => Err(AcelonError::NotProposedOwner),
         }
     }
 
@@ -45,66 +50,164 @@ impl AcelonOracleService {
         }
         let mut events = Vec::<Event>::new();
         for (i, data) in update_data.into_iter().enumerate() {
-            // 1. check the signatures
-            let mut valid_signers_counter = 0u8;
-            let signatures_to_check = &signatures[i];
-            let message_hash = blake2_256(&data);
-            for signature in signatures_to_check {
-                if let Ok(signer_pk) = secp256k1_ecdsa_recover_compressed(signature, &message_hash)
-                {
-                    let singer_account_id: AccountId = blake2_256(&signer_pk).into();
-                    let is_trusted = Storage::trusted_signers().get(&singer_account_id).is_some();
-                    if is_trusted {
-                        valid_signers_counter = valid_signers_counter.saturating_add(1);
-                        if valid_signers_counter >= Storage::config().valid_signers_threshold {
-                            break;
-                        }
-                    }
-                }
+            let price_payload = Self::verify_price_update(&data, &signatures[i])?;
+            events.extend(Self::set_price(price_payload));
+        }
+        Ok(events)
+    }
+
+    /// The valid time period of a feed, overridden by its feed config if any.
+    fn valid_time_period(request_hash: RequestHash) -> u64 {
+        Storage::feed_configs()
+            .get(&request_hash)
+            .and_then(|config| config.valid_time_period)
+            .unwrap_or(Storage::config().valid_time_period)
+    }
+
+    fn price_no_older_than(
+        request_hash: RequestHash,
+        age: u64,
+    ) -> Result<&'static PriceEntry, AcelonError> {
+        let now = exec::block_timestamp();
+        let price_feed = Storage::price_feeds()
+            .get(&request_hash)
+            .ok_or(AcelonError::PriceNotFound)?;
+        let price_time = price_feed.timestamp;
+        if now.saturating_sub(price_time) > age || price_feed.is_expired(now) {
+            return Err(AcelonError::PriceStale);
+        }
+        Ok(price_feed)
+    }
+
+    /// The signing domain binding updates to this program.
+    fn signing_domain() -> SigningDomain {
+        SigningDomain::new(exec::program_id().into_bytes())
+    }
+
+    /// Checks the signatures and the certificates of a single update and returns its decoded payload.
+    fn verify_price_update(
+        data: &[u8],
+        signatures_to_check: &[Signature],
+    ) -> Result<VersionedPricePayload, AcelonError> {
+        // the signed data has to be bound to this program, unless legacy payloads are accepted
+        let (signing_domain, payload_data) = SigningDomain::split(data);
+        match signing_domain {
+            Some(signing_domain) if signing_domain != Self::signing_domain() => {
+                return Err(AcelonError::InvalidSigningDomain);
             }
-            if valid_signers_counter < Storage::config().valid_signers_threshold {
-                return Err(AcelonError::NotEnoughValidSignatures);
+            None if Storage::config().require_signing_domain => {
+                return Err(AcelonError::InvalidSigningDomain);
             }
+            _ => {}
+        }
 
-            // 2. decode the data
-            let price_payload = PricePayload::decode(&mut data.as_slice())
-                .map_err(|_| AcelonError::InvalidPayload)?;
-
-            // 3. check certificates
-            let mut valid_sources_counter = 0u8;
-            for certificate in &price_payload.certificates {
-                let is_valid_certificate = Storage::certificate_trust_store()
-                    .get(certificate)
-                    .is_some();
-                if is_valid_certificate {
-                    valid_sources_counter = valid_sources_counter.saturating_add(1);
-                    if valid_sources_counter >= Storage::config().valid_sources_threshold {
+        // the payload is decoded upfront to apply the configuration of its feed,
+        // but it is only rejected as invalid once the signatures are checked
+        let decoded_payload = VersionedPricePayload::decode_from(payload_data);
+        let feed_config = decoded_payload
+            .as_ref()
+            .ok()
+            .and_then(|price_payload| Storage::feed_configs().get(&price_payload.request_hash()))
+            .copied()
+            .unwrap_or_default();
+
+        // 1. check the signatures, a signer is counted once however many times it signed
+        let mut valid_signers = BTreeSet::<AccountId>::new();
+        let message_hash = blake2_256(data);
+        let signers_threshold = feed_config
+            .valid_signers_threshold
+            .unwrap_or(Storage::config().valid_signers_threshold)
+            as usize;
+        for signature in signatures_to_check {
+            if let Ok(signer_pk) = secp256k1_ecdsa_recover_compressed(signature, &message_hash) {
+                let singer_account_id: AccountId = blake2_256(&signer_pk).into();
+                let is_trusted = Storage::trusted_signers().get(&singer_account_id).is_some();
+                if is_trusted {
+                    valid_signers.insert(singer_account_id);
+                    if valid_signers.len() >= signers_threshold {
                         break;
                     }
                 }
             }
-            if valid_sources_counter < Storage::config().valid_sources_threshold {
-                return Err(AcelonError::NotEnoughValidSources);
+        }
+        if valid_signers.len() < signers_threshold {
+            return Err(AcelonError::NotEnoughValidSignatures);
+        }
+
+        // 2. decode the data, check its feed is accepted and its timestamp against the block time
+        let price_payload = decoded_payload
+            .ok()
+            .filter(|price_payload| price_payload.is_consistent())
+            .ok_or(AcelonError::InvalidPayload)?;
+        if Storage::config().registered_feeds_only
+            && !Storage::registered_feeds().contains_key(&price_payload.request_hash())
+        {
+            return Err(AcelonError::FeedNotRegistered);
+        }
+        let now = exec::block_timestamp();
+        if let Some(max_future_drift) = Storage::config().max_future_drift {
+            if price_payload.timestamp() > now.saturating_add(max_future_drift) {
+                return Err(AcelonError::TimestampInFuture);
+            }
+        }
+        if let Some(max_submission_age) = Storage::config().max_submission_age {
+            if now.saturating_sub(price_payload.timestamp()) > max_submission_age {
+                return Err(AcelonError::TimestampTooOld);
             }
+        }
+        if let Some(valid_until) = price_payload.valid_until() {
+            if now > valid_until {
+                return Err(AcelonError::PayloadExpired);
+            }
+        }
 
-            // 4. set the new price
-            let is_more_recent = Storage::price_feeds()
-                .get(&price_payload.request_hash)
-                .map(|current_price| current_price.timestamp < price_payload.timestamp)
-                .unwrap_or(true);
-            if is_more_recent {
-                let price_entry = PriceEntry {
-                    timestamp: price_payload.timestamp,
-                    prices: price_payload.prices,
-                };
-                Storage::price_feeds().insert(price_payload.request_hash, price_entry.clone());
-                events.push(Event::PriceFeedUpdate {
-                    request_hash: price_payload.request_hash,
-                    price_entry,
-                });
+        // 3. check certificates, a certificate is counted once however many times it is listed
+        let mut valid_sources = BTreeSet::<&Certificate>::new();
+        let sources_threshold = feed_config
+            .valid_sources_threshold
+            .unwrap_or(Storage::config().valid_sources_threshold)
+            as usize;
+        for certificate in price_payload.certificates() {
+            let is_valid_certificate = Storage::certificate_trust_store()
+                .get(certificate)
+                .is_some();
+            if is_valid_certificate {
+                valid_sources.insert(certificate);
+                if valid_sources.len() >= sources_threshold {
+                    break;
+                }
             }
         }
-        Ok(events)
+        if valid_sources.len() < sources_threshold {
+            return Err(AcelonError::NotEnoughValidSources);
+        }
+
+        Ok(price_payload)
+    }
+
+    /// Stores the price of a verified payload if it is more recent than the current one.
+    ///
+    /// Payloads with the same timestamp are ordered by their sequence number, V1 payloads having none.
+    /// Returns the event to notify if the price was stored.
+    fn set_price(price_payload: VersionedPricePayload) -> Option<Event> {
+        // 4. set the new price
+        let request_hash = price_payload.request_hash();
+        let is_more_recent = Storage::price_feeds()
+            .get(&request_hash)
+            .map(|current_price| {
+                (current_price.timestamp, current_price.sequence)
+                    < (price_payload.timestamp(), price_payload.sequence())
+            })
+            .unwrap_or(true);
+        if !is_more_recent {
+            return None;
+        }
+        let price_entry = price_payload.into_price_entry();
+        Storage::price_feeds().insert(request_hash, price_entry.clone());
+        Some(Event::PriceFeedUpdate {
+            request_hash,
+            price_entry,
+        })
     }
 }
 
@@ -137,6 +240,30 @@ impl AcelonOracleService {
                     config.valid_time_period = new_time_period;
                     let _ = self.notify_on(Event::ValidTimePeriodUpdate { new_time_period });
                 }
+                ConfigureArgument::MaxFutureDrift(max_future_drift) => {
+                    config.max_future_drift = max_future_drift;
+                    let _ = self.notify_on(Event::MaxFutureDriftUpdate { max_future_drift });
+                }
+                ConfigureArgument::MaxSubmissionAge(max_submission_age) => {
+                    config.max_submission_age = max_submission_age;
+                    let _ = self.notify_on(Event::MaxSubmissionAgeUpdate { max_submission_age });
+                }
+                ConfigureArgument::RegisteredFeedsOnly(registered_feeds_only) => {
+                    config.registered_feeds_only = registered_feeds_only;
+                    let _ = self.notify_on(Event::RegisteredFeedsOnlyUpdate {
+                        registered_feeds_only,
+                    });
+                }
+                ConfigureArgument::RequireSigningDomain(require_signing_domain) => {
+                    config.require_signing_domain = require_signing_domain;
+                    let _ = self.notify_on(Event::RequireSigningDomainUpdate {
+                        require_signing_domain,
+                    });
+                }
+                ConfigureArgument::OwnerProposalTtl(owner_proposal_ttl) => {
+                    config.owner_proposal_ttl = owner_proposal_ttl;
+                    let _ = self.notify_on(Event::OwnerProposalTtlUpdate { owner_proposal_ttl });
+                }
             }
         }
     }
@@ -152,6 +279,41 @@ impl AcelonOracleService {
         }
     }
 
+    /// Applies every valid update of the batch and returns the result of each update.
+    pub fn try_update_price_feeds(
+        &mut self,
+        update_data: Vec<Vec<u8>>,
+        signatures: Vec<Vec<Signature>>,
+    ) -> Vec<PriceUpdateResult> {
+        let mut results = Vec::with_capacity(update_data.len());
+        for (i, data) in update_data.into_iter().enumerate() {
+            let signatures_to_check = signatures.get(i).map(Vec::as_slice).unwrap_or_default();
+            let result = match Self::verify_price_update(&data, signatures_to_check) {
+                Ok(price_payload) => match Self::set_price(price_payload) {
+                    Some(event) => {
+                        let _ = self.notify_on(event);
+                        PriceUpdateResult::Updated
+                    }
+                    None => PriceUpdateResult::NotMoreRecent,
+                },
+                Err(reason) => {
+                    let _ = self.notify_on(Event::PriceFeedRejected {
+                        index: i as u32,
+                        request_hash: VersionedPricePayload::decode_from(
+                            SigningDomain::split(&data).1,
+                        )
+                        .ok()
+                        .map(|price_payload| price_payload.request_hash()),
+                        reason,
+                    });
+                    PriceUpdateResult::Rejected(reason)
+                }
+            };
+            results.push(result);
+        }
+        results
+    }
+
     pub fn price_feed_exists(&self, request_hash: RequestHash) -> bool {
         Storage::price_feeds()
             .get(&request_hash)
@@ -159,12 +321,39 @@ impl AcelonOracleService {
             .unwrap_or(false)
     }
 
+    /// The signing domain binding updates to this program.
+    pub fn get_signing_domain(&self) -> SigningDomain {
+        Self::signing_domain()
+    }
+
     pub fn get_valid_time_period(&self) -> u64 {
         Storage::config().valid_time_period
     }
 
+    /// The configuration overrides of a feed, if any.
+    pub fn get_feed_config(&self, request_hash: RequestHash) -> Option<&'static FeedConfig> {
+        Storage::feed_configs().get(&request_hash)
+    }
+
+    /// Overrides the global thresholds and valid time period for a feed.
+    pub fn set_feed_config(&mut self, request_hash: RequestHash, config: FeedConfig) {
+        panicking(Self::ensure_owner);
+        Storage::feed_configs().insert(request_hash, config);
+        let _ = self.notify_on(Event::FeedConfigSet {
+            request_hash,
+            config,
+        });
+    }
+
+    /// Removes the overrides of a feed, which falls back to the global configuration.
+    pub fn clear_feed_config(&mut self, request_hash: RequestHash) {
+        panicking(Self::ensure_owner);
+        Storage::feed_configs().remove(&request_hash);
+        let _ = self.notify_on(Event::FeedConfigCleared { request_hash });
+    }
+
     pub fn get_price(&self, request_hash: RequestHash) -> Option<&'static PriceEntry> {
-        self.get_price_no_holder_than(request_hash, Storage::config().valid_time_period)
+        self.get_price_no_holder_than(request_hash, Self::valid_time_period(request_hash))
     }
 
     pub fn get_price_no_holder_than(
@@ -172,15 +361,38 @@ impl AcelonOracleService {
         request_hash: RequestHash,
         age: u64,
     ) -> Option<&'static PriceEntry> {
-        let now = exec::block_timestamp();
-        let price_feed = Storage::price_feeds().get(&request_hash);
-        if let Some(price_feed) = price_feed {
-            let price_time = price_feed.timestamp;
-            if diff(price_time, now) <= age {
-                return Some(price_feed);
-            }
-        }
-        None
+        Self::price_no_older_than(request_hash, age).ok()
+    }
+
+    /// Returns the price of each feed if it is not older than its valid time period, in the order
+    /// of `request_hashes`.
+    ///
+    /// A missing price fails with `AcelonError::PriceNotFound` if it was never set, or with
+    /// `AcelonError::PriceStale` if it is too old or expired.
+    pub fn get_prices(
+        &self,
+        request_hashes: Vec<RequestHash>,
+    ) -> Vec<Result<PriceEntry, AcelonError>> {
+        request_hashes
+            .into_iter()
+            .map(|request_hash| {
+                Self::price_no_older_than(request_hash, Self::valid_time_period(request_hash))
+                    .cloned()
+            })
+            .collect()
+    }
+
+    /// Returns the price of each feed if it is not older than `age` milliseconds, in the order of
+    /// `request_hashes`.
+    pub fn get_prices_no_older_than(
+        &self,
+        request_hashes: Vec<RequestHash>,
+        age: u64,
+    ) -> Vec<Result<PriceEntry, AcelonError>> {
+        request_hashes
+            .into_iter()
+            .map(|request_hash| Self::price_no_older_than(request_hash, age).cloned())
+            .collect()
     }
 
     pub fn update_trusted_signer(&mut self, update: TrustedSignerUpdate) {
@@ -209,14 +421,42 @@ impl AcelonOracleService {
         let _ = self.notify_on(Event::CertificateTrustStoreUpdated { update });
     }
 
+    pub fn update_registered_feed(&mut self, update: RegisteredFeedUpdate) {
+        panicking(Self::ensure_owner);
+        match update.operation {
+            ListUpdateOperation::Add => {
+                Storage::registered_feeds().insert(update.item, ());
+            }
+            ListUpdateOperation::Remove => {
+                Storage::registered_feeds().remove(&update.item);
+            }
+        }
+        let _ = self.notify_on(Event::RegisteredFeedUpdated { update });
+    }
+
+    pub fn is_feed_registered(&self, request_hash: RequestHash) -> bool {
+        Storage::registered_feeds().contains_key(&request_hash)
+    }
+
     pub fn propose_owner(&mut self, new_owner: AccountId) {
         panicking(Self::ensure_owner);
-        Storage::proposed_owners().insert(new_owner, ());
+        let expires_at =
+            exec::block_timestamp().saturating_add(Storage::config().owner_proposal_ttl);
+        Storage::proposed_owners().insert(new_owner, expires_at);
         let _ = self.notify_on(Event::OwnerProposed {
             proposed_owner: new_owner,
         });
     }
 
+    /// Withdraws the proposal of an owner that did not accept it yet.
+    pub fn cancel_owner_proposal(&mut self, proposed_owner: AccountId) {
+        panicking(Self::ensure_owner);
+        let maybe_cancelled = Storage::proposed_owners().remove(&proposed_owner);
+        if maybe_cancelled.is_some() {
+            let _ = self.notify_on(Event::OwnerProposalCancelled { proposed_owner });
+        }
+    }
+
     pub fn accept_owner(&mut self) {
         panicking(Self::ensure_proposed_owner);
         let caller = msg::source();
@@ -225,19 +465,32 @@ impl AcelonOracleService {
         let _ = self.notify_on(Event::OwnerAccepted);
     }
 
+    /// Removes an owner, unless it is the last one.
     pub fn remove_owner(&mut self, owner: AccountId) {
         panicking(Self::ensure_owner);
-        let maybe_removed_owner = Storage::owners().remove(&owner);
+        let owners = Storage::owners();
+        if owners.contains_key(&owner) && owners.len() <= 1 {
+            panic(AcelonError::LastOwner);
+        }
+        let maybe_removed_owner = owners.remove(&owner);
         if maybe_removed_owner.is_some() {
             let _ = self.notify_on(Event::OwnerRemoved { owner });
         }
     }
-}
 
-fn diff(a: u64, b: u64) -> u64 {
-    if a > b {
-        a.saturating_sub(b)
-    } else {
-        b.saturating_sub(a)
+    pub fn owners_count(&self) -> u32 {
+        Storage::owners().len() as u32
+    }
+
+    pub fn is_owner(&self, account: AccountId) -> bool {
+        Storage::owners().contains_key(&account)
+    }
+
+    /// Returns true if the account was proposed as owner and can still accept.
+    pub fn is_proposed_owner(&self, account: AccountId) -> bool {
+        Storage::proposed_owners()
+            .get(&account)
+            .map(|expires_at| *expires_at >= exec::block_timestamp())
+            .unwrap_or(false)
     }
 }
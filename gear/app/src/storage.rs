@@ -8,14 +8,17 @@ pub static mut STORAGE: Option<Storage> = None;
 #[derive(Debug, Default)]
 pub struct Storage {
     config: Config,
+    feed_configs: HashMap<RequestHash, FeedConfig>,
 
     trusted_signers: HashMap<AccountId, ()>,
     certificate_trust_store: HashMap<Certificate, ()>,
+    registered_feeds: HashMap<RequestHash, ()>,
 
     price_feeds: HashMap<RequestHash, PriceEntry>,
 
     owners: HashMap<AccountId, ()>,
-    proposed_owners: HashMap<AccountId, ()>,
+    /// The proposed owners with the timestamp after which they can no longer accept.
+    proposed_owners: HashMap<AccountId, u64>,
 }
 
 impl Storage {
@@ -32,6 +35,11 @@ impl Storage {
         &mut storage.config
     }
 
+    pub fn feed_configs() -> &'static mut HashMap<RequestHash, FeedConfig> {
+        let storage = Self::get_mut();
+        &mut storage.feed_configs
+    }
+
     pub fn trusted_signers() -> &'static mut HashMap<AccountId, ()> {
         let storage = Self::get_mut();
         &mut storage.trusted_signers
@@ -42,6 +50,11 @@ impl Storage {
         &mut storage.certificate_trust_store
     }
 
+    pub fn registered_feeds() -> &'static mut HashMap<RequestHash, ()> {
+        let storage = Self::get_mut();
+        &mut storage.registered_feeds
+    }
+
     pub fn price_feeds() -> &'static mut HashMap<RequestHash, PriceEntry> {
         let storage = Self::get_mut();
         &mut storage.price_feeds
@@ -52,7 +65,7 @@ impl Storage {
         &mut storage.owners
     }
 
-    pub fn proposed_owners() -> &'static mut HashMap<AccountId, ()> {
+    pub fn proposed_owners() -> &'static mut HashMap<AccountId, u64> {
         let storage = Self::get_mut();
         &mut storage.proposed_owners
     }
@@ -11,6 +11,11 @@ pub enum Event {
         request_hash: RequestHash,
         price_entry: PriceEntry,
     },
+    PriceFeedRejected {
+        index: u32,
+        request_hash: Option<RequestHash>,
+        reason: AcelonError,
+    },
     SignersThresholdUpdate {
         new_threshold: u8,
     },
@@ -20,15 +25,43 @@ pub enum Event {
     ValidTimePeriodUpdate {
         new_time_period: u64,
     },
+    MaxFutureDriftUpdate {
+        max_future_drift: Option<u64>,
+    },
+    MaxSubmissionAgeUpdate {
+        max_submission_age: Option<u64>,
+    },
+    RegisteredFeedsOnlyUpdate {
+        registered_feeds_only: bool,
+    },
+    RequireSigningDomainUpdate {
+        require_signing_domain: bool,
+    },
+    FeedConfigSet {
+        request_hash: RequestHash,
+        config: FeedConfig,
+    },
+    FeedConfigCleared {
+        request_hash: RequestHash,
+    },
     TrustedSignerUpdate {
         update: TrustedSignerUpdate,
     },
     CertificateTrustStoreUpdated {
         update: CertificateTrustStoreUpdate,
     },
+    RegisteredFeedUpdated {
+        update: RegisteredFeedUpdate,
+    },
+    OwnerProposalTtlUpdate {
+        owner_proposal_ttl: u64,
+    },
     OwnerProposed {
         proposed_owner: AccountId,
     },
+    OwnerProposalCancelled {
+        proposed_owner: AccountId,
+    },
     OwnerAccepted,
     OwnerRemoved {
         owner: AccountId,
@@ -41,6 +74,25 @@ pub struct Config {
     pub valid_sources_threshold: u8,
     pub valid_signers_threshold: u8,
     pub valid_time_period: u64,
+    /// How far in the future of the block time a payload timestamp can be, unchecked if `None`.
+    pub max_future_drift: Option<u64>,
+    /// How far in the past of the block time a payload timestamp can be, unchecked if `None`.
+    pub max_submission_age: Option<u64>,
+    /// When set, only updates of registered feeds are accepted.
+    pub registered_feeds_only: bool,
+    /// When set, legacy payloads are rejected and every update has to be bound to the program by a
+    /// [`SigningDomain`].
+    pub require_signing_domain: bool,
+    /// How long a proposed owner can accept the ownership, in milliseconds.
+    pub owner_proposal_ttl: u64,
+}
+
+/// Overrides of the global configuration for a single feed, `None` falls back to the global value.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo, Default)]
+pub struct FeedConfig {
+    pub valid_signers_threshold: Option<u8>,
+    pub valid_sources_threshold: Option<u8>,
+    pub valid_time_period: Option<u64>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
@@ -48,12 +100,79 @@ pub enum ConfigureArgument {
     SignersThreshold(u8),
     SourcesThreshold(u8),
     TimePeriod(u64),
+    MaxFutureDrift(Option<u64>),
+    MaxSubmissionAge(Option<u64>),
+    RegisteredFeedsOnly(bool),
+    RequireSigningDomain(bool),
+    OwnerProposalTtl(u64),
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo, Default)]
 pub struct PriceEntry {
     pub timestamp: u64,
     pub prices: Vec<u128>,
+    /// The decimals of each price, empty for prices of a V1 payload.
+    pub decimals: Vec<u8>,
+    /// The confidence of each price, empty for prices of a V1 payload.
+    pub confidences: Vec<u128>,
+    /// The price is stale after this timestamp, `None` for prices of a V1 payload.
+    pub valid_until: Option<u64>,
+    /// The sequence number of the payload, `None` for prices of a V1 payload.
+    pub sequence: Option<u64>,
+}
+
+impl PriceEntry {
+    /// Returns true if the price has an expiry before `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.valid_until
+            .map(|valid_until| valid_until < now)
+            .unwrap_or(false)
+    }
+}
+
+/// The bytes starting the signed data of an update bound to a [`SigningDomain`].
+pub const SIGNING_DOMAIN_TAG: [u8; 6] = *b"acelon";
+
+/// Binds the signed data of an update to a single program, so it cannot be replayed on other
+/// deployments trusting the same signers.
+///
+/// Bound data is `SIGNING_DOMAIN_TAG ++ SigningDomain ++ PricePayload`, SCALE encoded, and the
+/// signatures cover all of it. Data without the tag is a legacy payload.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct SigningDomain {
+    pub version: u8,
+    /// The id of the program.
+    pub target: [u8; 32],
+}
+
+impl SigningDomain {
+    /// The version of the signing domain accepted by the program.
+    pub const VERSION: u8 = 1;
+
+    pub fn new(target: [u8; 32]) -> Self {
+        Self {
+            version: Self::VERSION,
+            target,
+        }
+    }
+
+    /// Splits signed data into its signing domain, `None` for a legacy payload, and its payload.
+    pub fn split(data: &[u8]) -> (Option<Self>, &[u8]) {
+        if let Some(mut rest) = data.strip_prefix(SIGNING_DOMAIN_TAG.as_slice()) {
+            if let Ok(signing_domain) = Self::decode(&mut rest) {
+                return (Some(signing_domain), rest);
+            }
+        }
+        (None, data)
+    }
+
+    /// Returns the data to sign to bind `payload` to this domain.
+    pub fn bind(&self, payload: &[u8]) -> Vec<u8> {
+        let mut data = SIGNING_DOMAIN_TAG.to_vec();
+        self.encode_to(&mut data);
+        data.extend_from_slice(payload);
+        data
+    }
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
@@ -64,6 +183,122 @@ pub struct PricePayload {
     pub request_hash: RequestHash,
 }
 
+/// The bytes starting an encoded [`PricePayloadV2`].
+///
+/// `0xff` can't start a V1 payload, as it would announce a number of prices that does not fit a `u32`.
+pub const PRICE_PAYLOAD_V2_MAGIC: [u8; 4] = [0xff, b'a', b'p', 2];
+
+/// The second version of the price payload, encoded after [`PRICE_PAYLOAD_V2_MAGIC`].
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct PricePayloadV2 {
+    pub prices: Vec<u128>,
+    /// The decimals of each price.
+    pub decimals: Vec<u8>,
+    /// The confidence of each price, in the unit of the price.
+    pub confidences: Vec<u128>,
+    pub timestamp: u64,
+    /// The payload is rejected, and its price stale, after this timestamp.
+    pub valid_until: u64,
+    /// Orders the payloads of a feed with the same timestamp.
+    pub sequence: u64,
+    pub certificates: Vec<Certificate>,
+    pub request_hash: RequestHash,
+}
+
+/// A price payload of any supported version.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VersionedPricePayload {
+    V1(PricePayload),
+    V2(PricePayloadV2),
+}
+
+impl VersionedPricePayload {
+    /// Decodes a V2 payload if `data` starts with [`PRICE_PAYLOAD_V2_MAGIC`], a V1 payload otherwise.
+    pub fn decode_from(data: &[u8]) -> Result<Self, scale_codec::Error> {
+        match data.strip_prefix(PRICE_PAYLOAD_V2_MAGIC.as_slice()) {
+            Some(mut payload) => PricePayloadV2::decode(&mut payload).map(Self::V2),
+            None => PricePayload::decode(&mut &data[..]).map(Self::V1),
+        }
+    }
+
+    /// Encodes the payload, as read by [`Self::decode_from`].
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::V1(payload) => payload.encode(),
+            Self::V2(payload) => {
+                let mut data = PRICE_PAYLOAD_V2_MAGIC.to_vec();
+                payload.encode_to(&mut data);
+                data
+            }
+        }
+    }
+
+    /// Returns true if the decimals and the confidences, if any, match the prices.
+    pub fn is_consistent(&self) -> bool {
+        match self {
+            Self::V1(_) => true,
+            Self::V2(payload) => {
+                payload.decimals.len() == payload.prices.len()
+                    && payload.confidences.len() == payload.prices.len()
+            }
+        }
+    }
+
+    pub fn request_hash(&self) -> RequestHash {
+        match self {
+            Self::V1(payload) => payload.request_hash,
+            Self::V2(payload) => payload.request_hash,
+        }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Self::V1(payload) => payload.timestamp,
+            Self::V2(payload) => payload.timestamp,
+        }
+    }
+
+    pub fn certificates(&self) -> &[Certificate] {
+        match self {
+            Self::V1(payload) => &payload.certificates,
+            Self::V2(payload) => &payload.certificates,
+        }
+    }
+
+    pub fn valid_until(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.valid_until),
+        }
+    }
+
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.sequence),
+        }
+    }
+
+    /// The price entry stored for the payload.
+    pub fn into_price_entry(self) -> PriceEntry {
+        match self {
+            Self::V1(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                ..Default::default()
+            },
+            Self::V2(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                decimals: payload.decimals,
+                confidences: payload.confidences,
+                valid_until: Some(payload.valid_until),
+                sequence: Some(payload.sequence),
+            },
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Encode, Decode, TypeInfo)]
 pub enum AcelonError {
     NotEnoughValidSignatures,
@@ -72,6 +307,35 @@ pub enum AcelonError {
     NotOwner,
     NotProposedOwner,
     InvalidSignature,
+    /// The payload timestamp is further in the future than the max future drift.
+    TimestampInFuture,
+    /// The payload timestamp is older than the max submission age.
+    TimestampTooOld,
+    /// The registered feeds only mode is set and the feed is not registered.
+    FeedNotRegistered,
+    /// The data is bound to another deployment, or is a legacy payload while a signing domain is required.
+    InvalidSigningDomain,
+    /// The payload is past its `valid_until` timestamp.
+    PayloadExpired,
+    /// The last owner can't be removed.
+    LastOwner,
+    /// The owner proposal can no longer be accepted.
+    OwnerProposalExpired,
+    /// No price was ever set for the feed.
+    PriceNotFound,
+    /// The price of the feed is older than its valid time period, or expired.
+    PriceStale,
+}
+
+/// The outcome of a single price update of a `try_update_price_feeds` batch.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum PriceUpdateResult {
+    /// The price feed was updated.
+    Updated,
+    /// The update was valid, but the stored price is at least as recent.
+    NotMoreRecent,
+    /// The update was rejected.
+    Rejected(AcelonError),
 }
 
 /// The allowed sources update operation.
@@ -93,3 +357,4 @@ where
 
 pub type TrustedSignerUpdate = ListUpdate<AccountId>;
 pub type CertificateTrustStoreUpdate = ListUpdate<Certificate>;
+pub type RegisteredFeedUpdate = ListUpdate<RequestHash>;
@@ -1,16 +1,100 @@
 use sails_rs::{
     calls::*,
     gtest::{calls::*, System},
+    prelude::scale_codec::Encode,
     ActorId,
 };
 
-use acelon_oracle_app::utils::*;
+use acelon_oracle_app::{
+    types::{PricePayload, PricePayloadV2, VersionedPricePayload},
+    utils::*,
+};
 use acelon_oracle_client::{traits::*, *};
 
 use hex_literal::hex;
+use k256::ecdsa::SigningKey;
 
 const ACTOR_ID: u64 = 42;
 
+const PAYLOAD: [u8; 154] = hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+const SIGNATURE: [u8; 65] = hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b");
+const PAYLOAD_TIMESTAMP: u64 = 1_728_394_897_587;
+const REQUEST_HASH: [u8; 32] =
+    hex!("dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+const CERTIFICATE: [u8; 32] =
+    hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e");
+
+fn oracle() -> ActorId {
+    blake2_256(
+        hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020").as_slice(),
+    )
+    .into()
+}
+
+fn account_of(signing_key: &SigningKey) -> ActorId {
+    blake2_256(&signing_key.verifying_key().to_sec1_bytes()).into()
+}
+
+fn sign(signing_key: &SigningKey, payload: &[u8]) -> [u8; 65] {
+    let (signature, recovery_id) = signing_key
+        .sign_prehash_recoverable(&blake2_256(payload))
+        .unwrap();
+    let mut result = [0u8; 65];
+    result[..64].copy_from_slice(&signature.to_bytes());
+    result[64] = recovery_id.to_byte();
+    result
+}
+
+/// Deploys the program and configures it with the given thresholds, trusted signers and `CERTIFICATE`.
+async fn deploy(
+    signers_threshold: u8,
+    sources_threshold: u8,
+    trusted_signers: Vec<ActorId>,
+) -> (GTestRemoting, ActorId) {
+    let system = System::new();
+    system.init_logger();
+    system.mint_to(ACTOR_ID, 100_000_000_000_000);
+
+    let remoting = GTestRemoting::new(system, ACTOR_ID.into());
+    let program_code_id = remoting.system().submit_code(acelon_oracle::WASM_BINARY);
+    let program_id = acelon_oracle_client::AcelonOracleFactory::new(remoting.clone())
+        .new(ACTOR_ID.into())
+        .send_recv(program_code_id, b"salt")
+        .await
+        .unwrap();
+
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    service_client
+        .configure(vec![
+            ConfigureArgument::SignersThreshold(signers_threshold),
+            ConfigureArgument::SourcesThreshold(sources_threshold),
+            ConfigureArgument::TimePeriod(0),
+        ])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    for signer in trusted_signers {
+        service_client
+            .update_trusted_signer(ListUpdateForActorId {
+                operation: ListUpdateOperation::Add,
+                item: signer,
+            })
+            .send_recv(program_id)
+            .await
+            .unwrap();
+    }
+    service_client
+        .update_certificate_to_trust_store(ListUpdateForArrOf32U8 {
+            operation: ListUpdateOperation::Add,
+            item: CERTIFICATE,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap();
+
+    (remoting, program_id)
+}
+
 #[tokio::test]
 async fn test_update_price_feeds() {
     let system = System::new();
@@ -73,3 +157,490 @@ async fn test_update_price_feeds() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn test_duplicated_signatures_are_counted_once() {
+    let other_signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(2, 1, vec![oracle(), account_of(&other_signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+
+    let result = service_client
+        .update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE, SIGNATURE]])
+        .send_recv(program_id)
+        .await;
+    assert!(result.is_err());
+
+    service_client
+        .update_price_feeds(
+            vec![PAYLOAD.to_vec()],
+            vec![vec![SIGNATURE, sign(&other_signer, &PAYLOAD)]],
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_duplicated_certificates_are_counted_once() {
+    let signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(1, 2, vec![account_of(&signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+
+    let other_certificate =
+        hex!("4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384");
+    service_client
+        .update_certificate_to_trust_store(ListUpdateForArrOf32U8 {
+            operation: ListUpdateOperation::Add,
+            item: other_certificate,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap();
+
+    let payload = PricePayload {
+        prices: vec![1_000],
+        timestamp: PAYLOAD_TIMESTAMP,
+        certificates: vec![CERTIFICATE, CERTIFICATE],
+        request_hash: REQUEST_HASH,
+    }
+    .encode();
+    let results = service_client
+        .try_update_price_feeds(vec![payload.clone()], vec![vec![sign(&signer, &payload)]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [PriceUpdateResult::Rejected(
+            AcelonError::NotEnoughValidSources
+        )]
+    ));
+
+    let payload = PricePayload {
+        prices: vec![1_000],
+        timestamp: PAYLOAD_TIMESTAMP,
+        certificates: vec![CERTIFICATE, other_certificate],
+        request_hash: REQUEST_HASH,
+    }
+    .encode();
+    let results = service_client
+        .try_update_price_feeds(vec![payload.clone()], vec![vec![sign(&signer, &payload)]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(results.as_slice(), [PriceUpdateResult::Updated]));
+}
+
+#[tokio::test]
+async fn test_try_update_price_feeds_applies_valid_updates() {
+    let (remoting, program_id) = deploy(1, 1, vec![oracle()]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+
+    let results = service_client
+        .try_update_price_feeds(
+            vec![vec![1, 2, 3], PAYLOAD.to_vec()],
+            vec![vec![SIGNATURE], vec![SIGNATURE]],
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [
+            PriceUpdateResult::Rejected(AcelonError::NotEnoughValidSignatures),
+            PriceUpdateResult::Updated
+        ]
+    ));
+}
+
+#[tokio::test]
+async fn test_payload_timestamp_must_be_admissible() {
+    let signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(1, 1, vec![account_of(&signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    service_client
+        .configure(vec![
+            ConfigureArgument::MaxFutureDrift(Some(60_000)),
+            ConfigureArgument::MaxSubmissionAge(Some(60_000)),
+        ])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+
+    // the updates are processed a few blocks later, well within the one minute margins
+    let now = remoting.system().block_timestamp();
+    let update_data: Vec<_> = [now + 3_600_000, now - 3_600_000, now]
+        .into_iter()
+        .map(|timestamp| {
+            PricePayload {
+                prices: vec![1_000],
+                timestamp,
+                certificates: vec![CERTIFICATE],
+                request_hash: REQUEST_HASH,
+            }
+            .encode()
+        })
+        .collect();
+    let signatures = update_data
+        .iter()
+        .map(|data| vec![sign(&signer, data)])
+        .collect();
+    let results = service_client
+        .try_update_price_feeds(update_data, signatures)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [
+            PriceUpdateResult::Rejected(AcelonError::TimestampInFuture),
+            PriceUpdateResult::Rejected(AcelonError::TimestampTooOld),
+            PriceUpdateResult::Updated
+        ]
+    ));
+}
+
+#[tokio::test]
+async fn test_feed_config_overrides_global_config() {
+    let (remoting, program_id) = deploy(1, 1, vec![oracle()]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+
+    service_client
+        .set_feed_config(
+            REQUEST_HASH,
+            FeedConfig {
+                valid_signers_threshold: Some(2),
+                valid_sources_threshold: None,
+                valid_time_period: Some(u64::MAX),
+            },
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let stored_config = service_client
+        .get_feed_config(REQUEST_HASH)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        stored_config,
+        Some(FeedConfig {
+            valid_signers_threshold: Some(2),
+            ..
+        })
+    ));
+
+    let result = service_client
+        .update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]])
+        .send_recv(program_id)
+        .await;
+    assert!(result.is_err());
+
+    service_client
+        .set_feed_config(
+            REQUEST_HASH,
+            FeedConfig {
+                valid_signers_threshold: Some(1),
+                valid_sources_threshold: None,
+                valid_time_period: Some(u64::MAX),
+            },
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    service_client
+        .update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    // the overridden valid time period keeps the price valid whatever the block time
+    let price = service_client
+        .get_price(REQUEST_HASH)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(price.is_some());
+
+    service_client
+        .clear_feed_config(REQUEST_HASH)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let stored_config = service_client
+        .get_feed_config(REQUEST_HASH)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(stored_config.is_none());
+}
+
+#[tokio::test]
+async fn test_registered_feeds_only_rejects_unregistered_feeds() {
+    let (remoting, program_id) = deploy(1, 1, vec![oracle()]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    service_client
+        .configure(vec![ConfigureArgument::RegisteredFeedsOnly(true)])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+
+    let results = service_client
+        .try_update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [PriceUpdateResult::Rejected(AcelonError::FeedNotRegistered)]
+    ));
+
+    service_client
+        .update_registered_feed(ListUpdateForArrOf32U8 {
+            operation: ListUpdateOperation::Add,
+            item: REQUEST_HASH,
+        })
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let is_registered = service_client
+        .is_feed_registered(REQUEST_HASH)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(is_registered);
+    service_client
+        .update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_updates_are_bound_to_the_program() {
+    let signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(1, 1, vec![oracle(), account_of(&signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    let payload = PricePayload {
+        prices: vec![1_000],
+        timestamp: PAYLOAD_TIMESTAMP,
+        certificates: vec![CERTIFICATE],
+        request_hash: REQUEST_HASH,
+    }
+    .encode();
+
+    let other_domain = acelon_oracle_app::types::SigningDomain::new([1u8; 32]).bind(&payload);
+    let results = service_client
+        .try_update_price_feeds(
+            vec![other_domain.clone()],
+            vec![vec![sign(&signer, &other_domain)]],
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [PriceUpdateResult::Rejected(
+            AcelonError::InvalidSigningDomain
+        )]
+    ));
+
+    // legacy payloads are accepted until a signing domain is required
+    service_client
+        .configure(vec![ConfigureArgument::RequireSigningDomain(true)])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let results = service_client
+        .try_update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [PriceUpdateResult::Rejected(
+            AcelonError::InvalidSigningDomain
+        )]
+    ));
+
+    let data = acelon_oracle_app::types::SigningDomain::new(program_id.into_bytes()).bind(&payload);
+    service_client
+        .update_price_feeds(vec![data.clone()], vec![vec![sign(&signer, &data)]])
+        .send_recv(program_id)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_price_payload_v2() {
+    let signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(1, 1, vec![account_of(&signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    let payload = |decimals: Vec<u8>, valid_until: u64, sequence: u64| {
+        VersionedPricePayload::V2(PricePayloadV2 {
+            prices: vec![1_000],
+            decimals,
+            confidences: vec![10],
+            timestamp: PAYLOAD_TIMESTAMP,
+            valid_until,
+            sequence,
+            certificates: vec![CERTIFICATE],
+            request_hash: REQUEST_HASH,
+        })
+        .encode()
+    };
+
+    // expired, inconsistent, valid, not more recent and more recent by sequence
+    let update_data = vec![
+        payload(vec![8], 0, 1),
+        payload(vec![], u64::MAX, 1),
+        payload(vec![8], u64::MAX, 1),
+        payload(vec![8], u64::MAX, 1),
+        payload(vec![8], u64::MAX, 2),
+    ];
+    let signatures = update_data
+        .iter()
+        .map(|data| vec![sign(&signer, data)])
+        .collect();
+    let results = service_client
+        .try_update_price_feeds(update_data, signatures)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        results.as_slice(),
+        [
+            PriceUpdateResult::Rejected(AcelonError::PayloadExpired),
+            PriceUpdateResult::Rejected(AcelonError::InvalidPayload),
+            PriceUpdateResult::Updated,
+            PriceUpdateResult::NotMoreRecent,
+            PriceUpdateResult::Updated
+        ]
+    ));
+
+    let price = service_client
+        .get_price_no_holder_than(REQUEST_HASH, u64::MAX)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        price,
+        Some(PriceEntry {
+            valid_until: Some(u64::MAX),
+            sequence: Some(2),
+            ..
+        })
+    ));
+}
+
+#[tokio::test]
+async fn test_get_prices_tells_why_a_price_is_missing() {
+    let signer = SigningKey::from_slice(&[1u8; 32]).unwrap();
+    let (remoting, program_id) = deploy(1, 1, vec![oracle(), account_of(&signer)]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+
+    // `PAYLOAD` is older than the block time, another feed gets a price at the block time
+    let fresh_request_hash = [1; 32];
+    let fresh_payload = PricePayload {
+        prices: vec![2_000],
+        timestamp: remoting.system().block_timestamp(),
+        certificates: vec![CERTIFICATE],
+        request_hash: fresh_request_hash,
+    }
+    .encode();
+    service_client
+        .update_price_feeds(
+            vec![PAYLOAD.to_vec(), fresh_payload.clone()],
+            vec![vec![SIGNATURE], vec![sign(&signer, &fresh_payload)]],
+        )
+        .send_recv(program_id)
+        .await
+        .unwrap();
+
+    let prices = service_client
+        .get_prices_no_older_than(vec![REQUEST_HASH, [0; 32]], u64::MAX)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        prices.as_slice(),
+        [
+            Ok(PriceEntry {
+                timestamp: PAYLOAD_TIMESTAMP,
+                ..
+            }),
+            Err(AcelonError::PriceNotFound)
+        ]
+    ));
+
+    // the results follow the order of the request
+    let prices = service_client
+        .get_prices_no_older_than(
+            vec![REQUEST_HASH, [0; 32], fresh_request_hash, REQUEST_HASH],
+            60_000,
+        )
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(matches!(
+        prices.as_slice(),
+        [
+            Err(AcelonError::PriceStale),
+            Err(AcelonError::PriceNotFound),
+            Ok(PriceEntry {
+                prices: fresh_prices,
+                ..
+            }),
+            Err(AcelonError::PriceStale)
+        ] if fresh_prices == &[2_000]
+    ));
+}
+
+#[tokio::test]
+async fn test_last_owner_cannot_be_removed() {
+    let (remoting, program_id) = deploy(1, 1, vec![oracle()]).await;
+    let mut service_client = acelon_oracle_client::OracleService::new(remoting.clone());
+    let owner: ActorId = ACTOR_ID.into();
+    let proposed_owner: ActorId = [1u8; 32].into();
+
+    let result = service_client
+        .remove_owner(owner)
+        .send_recv(program_id)
+        .await;
+    assert!(result.is_err());
+    let is_owner = service_client
+        .is_owner(owner)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(is_owner);
+    let owners_count = service_client
+        .owners_count()
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert_eq!(owners_count, 1);
+
+    // a pending owner proposal can be cancelled
+    service_client
+        .propose_owner(proposed_owner)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let is_proposed_owner = service_client
+        .is_proposed_owner(proposed_owner)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(is_proposed_owner);
+    service_client
+        .cancel_owner_proposal(proposed_owner)
+        .send_recv(program_id)
+        .await
+        .unwrap();
+    let is_proposed_owner = service_client
+        .is_proposed_owner(proposed_owner)
+        .recv(program_id)
+        .await
+        .unwrap();
+    assert!(!is_proposed_owner);
+}
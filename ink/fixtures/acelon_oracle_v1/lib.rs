@@ -0,0 +1,101 @@
+//! The storage layout of the first deployments of the oracle, the storage version 1, to test the
+//! upgrades from it.
+//!
+//! Only the messages needed to fill its storage are kept.
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+pub use self::acelon_oracle_v1::{AcelonOracle, AcelonOracleRef};
+
+#[ink::contract]
+mod acelon_oracle_v1 {
+    use ink::{prelude::vec::Vec, storage::Mapping};
+    use scale::{Decode, Encode};
+    use scale_info::TypeInfo;
+
+    pub type Certificate = [u8; 32];
+    pub type RequestHash = [u8; 32];
+
+    #[derive(Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+    #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+    pub struct PriceEntry {
+        pub timestamp: u64,
+        pub prices: Vec<u128>,
+    }
+
+    /// Named as the current contract, the storage keys of its mappings are derived from it.
+    #[ink(storage)]
+    pub struct AcelonOracle {
+        valid_sources_threshold: u8,
+        valid_signers_threshold: u8,
+        valid_time_period: u64,
+
+        trusted_signers: Mapping<AccountId, bool>,
+        certificate_trust_store: Mapping<Certificate, bool>,
+
+        price_feeds: Mapping<RequestHash, PriceEntry>,
+
+        owners: Mapping<AccountId, ()>,
+        proposed_owners: Mapping<AccountId, ()>,
+    }
+
+    impl AcelonOracle {
+        #[ink(constructor)]
+        pub fn new(
+            owners: Vec<AccountId>,
+            trusted_signers: Vec<AccountId>,
+            certificate_trust_store: Vec<Certificate>,
+            valid_signers_threshold: u8,
+            valid_sources_threshold: u8,
+            valid_time_period: u64,
+        ) -> Self {
+            let mut signers = Mapping::default();
+            for signer in trusted_signers {
+                signers.insert(signer, &true);
+            }
+
+            let mut trust_store = Mapping::default();
+            for cert in certificate_trust_store {
+                trust_store.insert(cert, &true);
+            }
+
+            let mut owners_map = Mapping::default();
+            for owner in owners {
+                owners_map.insert(owner, &());
+            }
+
+            Self {
+                valid_sources_threshold,
+                valid_signers_threshold,
+                valid_time_period,
+                trusted_signers: signers,
+                certificate_trust_store: trust_store,
+                price_feeds: Mapping::default(),
+                owners: owners_map,
+                proposed_owners: Mapping::default(),
+            }
+        }
+
+        /// Stores a price as an update verified by the first version would have.
+        #[ink(message)]
+        pub fn set_price(&mut self, request_hash: RequestHash, timestamp: u64, prices: Vec<u128>) {
+            self.price_feeds
+                .insert(request_hash, &PriceEntry { timestamp, prices });
+        }
+
+        #[ink(message)]
+        pub fn propose_owner(&mut self, new_owner: AccountId) {
+            self.proposed_owners.insert(new_owner, &());
+        }
+
+        /// Replaces the code of the contract.
+        ///
+        /// The first version has no upgrade message, this stands in for the `Contracts::set_code`
+        /// call its deployments are upgraded with.
+        #[ink(message)]
+        pub fn set_code(&mut self, code_hash: Hash) {
+            self.env()
+                .set_code_hash(&code_hash)
+                .expect("the code is uploaded");
+        }
+    }
+}
@@ -3,30 +3,43 @@
 mod traits;
 mod types;
 
-fn diff(a: u64, b: u64) -> u64 {
-    if a > b {
-        a.saturating_sub(b)
-    } else {
-        b.saturating_sub(a)
-    }
-}
-
 #[ink::contract]
 mod acelon_oracle {
-    use ink::{env::hash::Blake2x256, prelude::vec::Vec, storage::Mapping};
-    use scale::Decode;
+    use ink::{
+        env::hash::Blake2x256,
+        prelude::{collections::BTreeSet, vec, vec::Vec},
+        storage::{traits::StorageKey, Lazy, Mapping},
+    };
 
     use crate::{
         traits::PriceOracle,
-        types::{Certificate, Error, PriceEntry, PricePayload, RequestHash, Signature},
+        types::{
+            AdminAction, Certificate, Config, Error, FeedConfig, PauseScope, PriceEntry,
+            PriceEntryV1, PriceUpdateResult, Proposal, RequestHash, Signature, SigningDomain,
+            VersionedPricePayload,
+        },
     };
 
+    /// The version of the storage layout written by this code.
+    ///
+    /// - 1: the first deployments, without [`Config`] and with price entries without the fields of
+    ///   the V2 payloads, see [`PriceEntryV1`].
+    /// - 2: the current layout.
+    const STORAGE_VERSION: u32 = 2;
+
     #[ink(event)]
     pub struct PriceFeedUpdate {
         request_hash: RequestHash,
         price_entry: PriceEntry,
     }
 
+    #[ink(event)]
+    pub struct PriceFeedRejected {
+        index: u32,
+        request_hash: Option<RequestHash>,
+        reason: Error,
+    }
+
     #[ink(event)]
     pub struct SignersThresholdUpdate {
         new_threshold: u8,
@@ -42,6 +55,47 @@ mod acelon_oracle {
         new_time_period: u64,
     }
 
+    #[ink(event)]
+    pub struct MaxFutureDriftUpdate {
+        max_future_drift: Option<u64>,
+    }
+
+    #[ink(event)]
+    pub struct MaxSubmissionAgeUpdate {
+        max_submission_age: Option<u64>,
+    }
+
+    #[ink(event)]
+    pub struct RegisteredFeedsOnlyUpdate {
+        registered_feeds_only: bool,
+    }
+
+    #[ink(event)]
+    pub struct RequireSigningDomainUpdate {
+        require_signing_domain: bool,
+    }
+
+    #[ink(event)]
+    pub struct FeedRegistered {
+        request_hash: RequestHash,
+    }
+
+    #[ink(event)]
+    pub struct FeedDeregistered {
+        request_hash: RequestHash,
+    }
+
+    #[ink(event)]
+    pub struct FeedConfigSet {
+        request_hash: RequestHash,
+        config: FeedConfig,
+    }
+
+    #[ink(event)]
+    pub struct FeedConfigCleared {
+        request_hash: RequestHash,
+    }
+
     #[ink(event)]
     pub struct TrustedSignerAdded {
         new_trusted_signer: AccountId,
@@ -62,11 +116,81 @@ mod acelon_oracle {
         certificate: Certificate,
     }
 
+    #[ink(event)]
+    pub struct Paused {
+        scope: PauseScope,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        scope: PauseScope,
+    }
+
+    #[ink(event)]
+    pub struct GuardianAdded {
+        guardian: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct GuardianRemoved {
+        guardian: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct QuorumUpdate {
+        quorum: u8,
+        defensive_quorum: u8,
+    }
+
+    #[ink(event)]
+    pub struct ProposalTtlUpdate {
+        proposal_ttl: u64,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCreated {
+        proposal_id: u32,
+        proposer: AccountId,
+        action: AdminAction,
+    }
+
+    #[ink(event)]
+    pub struct ProposalApproved {
+        proposal_id: u32,
+        approver: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct ProposalExecuted {
+        proposal_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct ProposalCancelled {
+        proposal_id: u32,
+    }
+
+    #[ink(event)]
+    pub struct Upgraded {
+        code_hash: Hash,
+    }
+
+    #[ink(event)]
+    pub struct Migrated {
+        from_version: u32,
+        to_version: u32,
+    }
+
     #[ink(event)]
     pub struct OwnerProposed {
         proposed_owner: AccountId,
     }
 
+    #[ink(event)]
+    pub struct OwnerProposalCancelled {
+        proposed_owner: AccountId,
+    }
+
     #[ink(event)]
     pub struct OwnerAccepted;
 
@@ -75,11 +199,18 @@ mod acelon_oracle {
         owner: AccountId,
     }
 
+    /// The fields stored in the root of the contract are the ones of the first storage version, new
+    /// fields have to be `Mapping`s or `Lazy`s so the upgraded deployments can still decode it.
     #[ink(storage)]
     pub struct AcelonOracle {
         valid_sources_threshold: u8,
         valid_signers_threshold: u8,
         valid_time_period: u64,
+        /// Absent until `migrate` is called on the deployments of the first storage version.
+        config: Lazy<Config>,
+        registered_feeds: Mapping<RequestHash, ()>,
+        /// Per feed overrides of the global configuration.
+        feed_configs: Mapping<RequestHash, FeedConfig>,
 
         trusted_signers: Mapping<AccountId, bool>,
         certificate_trust_store: Mapping<Certificate, bool>,
@@ -87,10 +218,22 @@ mod acelon_oracle {
         price_feeds: Mapping<RequestHash, PriceEntry>,
 
         owners: Mapping<AccountId, ()>,
-        proposed_owners: Mapping<AccountId, ()>,
+        /// The proposed owners with the timestamp after which they can no longer accept.
+        proposed_owners: Mapping<AccountId, u64>,
+        /// Accounts allowed to pause the oracle, but not to unpause or configure it.
+        guardians: Mapping<AccountId, ()>,
+        proposals: Mapping<u32, Proposal>,
+
+        /// Feeds whose updates are rejected and price reads fail.
+        paused_feeds: Mapping<RequestHash, ()>,
+
+        /// The version of the storage layout, behind [`STORAGE_VERSION`] until `migrate` is called
+        /// after an upgrade, and absent on the deployments of the first version.
+        storage_version: Lazy<u32>,
     }
 
     impl AcelonOracle {
+        /// Creates the oracle, owned by the caller if `owners` is empty.
         #[ink(constructor)]
         pub fn new(
             owners: Vec<AccountId>,
@@ -112,20 +255,43 @@ mod acelon_oracle {
             }
 
             let mut owners_map = Mapping::default();
+            let mut owners_count = 0;
 
+            let owners = if owners.is_empty() {
+                vec![Self::env().caller()]
+            } else {
+                owners
+            };
             for owner in owners {
-                owners_map.insert(owner, &());
+                if owners_map.insert(owner, &()).is_none() {
+                    owners_count += 1;
+                }
             }
 
+            let mut config = Lazy::new();
+            config.set(&Config {
+                owners_count,
+                ..Config::default()
+            });
+            let mut storage_version = Lazy::new();
+            storage_version.set(&STORAGE_VERSION);
+
             Self {
                 valid_sources_threshold,
                 valid_signers_threshold,
                 valid_time_period,
+                config,
+                registered_feeds: Mapping::default(),
+                feed_configs: Mapping::default(),
                 trusted_signers: signers,
                 certificate_trust_store: trust_store,
                 price_feeds: Mapping::default(),
                 owners: owners_map,
                 proposed_owners: Mapping::default(),
+                guardians: Mapping::default(),
+                proposals: Mapping::default(),
+                paused_feeds: Mapping::default(),
+                storage_version,
             }
         }
 
@@ -143,285 +309,1772 @@ mod acelon_oracle {
 
         #[ink(message)]
         pub fn propose_owner(&mut self, new_owner: AccountId) -> Result<(), Error> {
-            self.ensure_owner()?;
-            self.proposed_owners.insert(new_owner, &());
-            self.env().emit_event(OwnerProposed {
-                proposed_owner: new_owner,
-            });
-            Ok(())
+            self.execute_as_owner(AdminAction::ProposeOwner(new_owner))
         }
 
         #[ink(message)]
         pub fn accept_owner(&mut self) -> Result<(), Error> {
             let caller = self.env().caller();
-            let maybe_proposed = self.proposed_owners.take(caller);
-            if maybe_proposed.is_none() {
-                return Err(Error::NotProposedOwner);
+            let expires_at = self
+                .proposal_expiry(caller)
+                .ok_or(Error::NotProposedOwner)?;
+            self.proposed_owners.remove(caller);
+            if expires_at < self.env().block_timestamp() {
+                return Err(Error::OwnerProposalExpired);
+            }
+            if self.owners.insert(caller, &()).is_none() {
+                let mut config = self.config();
+                config.owners_count += 1;
+                self.config.set(&config);
             }
-            self.owners.insert(caller, &());
             self.env().emit_event(OwnerAccepted);
             Ok(())
         }
 
+        /// Withdraws the proposal of an owner that did not accept it yet.
+        #[ink(message)]
+        pub fn cancel_owner_proposal(&mut self, proposed_owner: AccountId) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::CancelOwnerProposal(proposed_owner))
+        }
+
+        #[ink(message)]
+        pub fn get_owners_count(&self) -> u32 {
+            self.config().owners_count
+        }
+
+        #[ink(message)]
+        pub fn is_owner(&self, account: AccountId) -> bool {
+            self.owners.contains(account)
+        }
+
+        /// Returns true if the account was proposed as owner and can still accept.
+        #[ink(message)]
+        pub fn is_proposed_owner(&self, account: AccountId) -> bool {
+            self.proposal_expiry(account)
+                .map(|expires_at| expires_at >= self.env().block_timestamp())
+                .unwrap_or(false)
+        }
+
+        /// Removes an owner, unless it is the last one or too few owners would be left to reach the quorum.
         #[ink(message)]
         pub fn remove_owner(&mut self, owner: AccountId) -> Result<(), Error> {
-            self.ensure_owner()?;
-            let maybe_removed_owner = self.owners.take(owner);
-            if maybe_removed_owner.is_some() {
-                self.env().emit_event(OwnerRemoved { owner })
-            }
-            Ok(())
+            self.execute_as_owner(AdminAction::RemoveOwner(owner))
         }
 
-        fn ensure_owner(&self) -> Result<(), Error> {
-            let caller = self.env().caller();
-            let maybe_owner = self.owners.get(caller);
-            if maybe_owner.is_none() {
-                return Err(Error::NotOwner);
-            }
-            Ok(())
+        #[ink(message)]
+        pub fn add_guardian(&mut self, guardian: AccountId) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::AddGuardian(guardian))
         }
-    }
 
-    impl PriceOracle for AcelonOracle {
         #[ink(message)]
-        fn update_price_feeds(
-            &mut self,
-            update_data: Vec<Vec<u8>>,
-            signatures: Vec<Vec<Signature>>,
-        ) -> Result<(), Error> {
-            if signatures.len() < update_data.len() {
-                return Err(Error::NotEnoughValidSignatures);
-            }
-            for (i, data) in update_data.into_iter().enumerate() {
-                // 1. check the signatures
-                let mut valid_signers_counter = 0u8;
-                let signatures_to_check = &signatures[i];
-                let message_hash = self.env().hash_bytes::<Blake2x256>(&data);
-                for signature in signatures_to_check {
-                    if let Ok(signer_pk) = self.env().ecdsa_recover(signature, &message_hash) {
-                        let signer_account_id: AccountId =
-                            self.env().hash_bytes::<Blake2x256>(&signer_pk).into();
-                        let is_trusted =
-                            self.trusted_signers.get(signer_account_id).unwrap_or(false);
-                        if is_trusted {
-                            valid_signers_counter = valid_signers_counter.saturating_add(1);
-                            if valid_signers_counter >= self.valid_signers_threshold {
-                                break;
-                            }
-                        }
-                    }
-                }
-                if valid_signers_counter < self.valid_signers_threshold {
-                    return Err(Error::NotEnoughValidSignatures);
-                }
+        pub fn remove_guardian(&mut self, guardian: AccountId) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::RemoveGuardian(guardian))
+        }
 
-                // 2. decode the data
-                let price_paylod = PricePayload::decode(&mut data.as_slice())
-                    .map_err(|_| Error::InvalidPayload)?;
+        #[ink(message)]
+        pub fn is_guardian(&self, account: AccountId) -> bool {
+            self.guardians.contains(account)
+        }
 
-                // 3. check certificates
-                let mut valid_sources_counter = 0u8;
-                for certificate in &price_paylod.certificates {
-                    let is_valid_certificate = self
-                        .certificate_trust_store
-                        .get(certificate)
-                        .unwrap_or(false);
-                    if is_valid_certificate {
-                        valid_sources_counter = valid_sources_counter.saturating_add(1);
-                        if valid_sources_counter >= self.valid_sources_threshold {
-                            break;
-                        }
-                    }
+        /// Pauses the whole oracle or some feeds, callable by the owners and the guardians.
+        #[ink(message)]
+        pub fn pause(&mut self, scope: PauseScope) -> Result<(), Error> {
+            self.ensure_owner_or_guardian()?;
+            match &scope {
+                PauseScope::All => {
+                    let mut config = self.config();
+                    config.paused = true;
+                    self.config.set(&config);
                 }
-                if valid_sources_counter < self.valid_sources_threshold {
-                    return Err(Error::NotEnoughValidSources);
-                }
-
-                // 4. set the new price
-                let is_more_recent = self
-                    .price_feeds
-                    .get(price_paylod.request_hash)
-                    .map(|curret_price| curret_price.timestamp < price_paylod.timestamp)
-                    .unwrap_or(true);
-                if is_more_recent {
-                    let price_entry = PriceEntry {
-                        timestamp: price_paylod.timestamp,
-                        prices: price_paylod.prices,
-                    };
-                    self.price_feeds
-                        .insert(price_paylod.request_hash, &price_entry);
-                    self.env().emit_event(PriceFeedUpdate {
-                        request_hash: price_paylod.request_hash,
-                        price_entry,
-                    });
+                PauseScope::Feeds(request_hashes) => {
+                    for request_hash in request_hashes {
+                        self.paused_feeds.insert(request_hash, &());
+                    }
                 }
             }
+            self.env().emit_event(Paused { scope });
             Ok(())
         }
 
+        /// Lifts a pause, callable by the owners only.
+        ///
+        /// Unpausing `PauseScope::All` does not unpause the feeds paused with `PauseScope::Feeds`.
         #[ink(message)]
-        fn price_feed_exists(&self, request_hash: RequestHash) -> bool {
-            self.price_feeds
-                .get(request_hash)
-                .map(|value| value.timestamp > 0)
-                .unwrap_or(false)
+        pub fn unpause(&mut self, scope: PauseScope) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::Unpause(scope))
         }
 
         #[ink(message)]
-        fn get_valid_time_period(&self) -> u64 {
-            self.valid_time_period
+        pub fn is_paused(&self) -> bool {
+            self.config().paused
         }
 
+        /// Returns true if the oracle or the feed is paused.
         #[ink(message)]
-        fn get_price(&self, request_hash: RequestHash) -> Option<PriceEntry> {
-            self.get_price_no_holder_than(request_hash, self.valid_time_period)
+        pub fn is_feed_paused(&self, request_hash: RequestHash) -> bool {
+            self.config().paused || self.paused_feeds.contains(request_hash)
         }
 
+        /// Proposes an admin action, approved by the caller, and executes it once it reaches its quorum.
+        ///
+        /// Returns the id of the proposal.
         #[ink(message)]
-        fn get_price_no_holder_than(
-            &self,
-            request_hash: RequestHash,
-            age: u64,
-        ) -> Option<PriceEntry> {
-            let now = self.env().block_timestamp();
-            let price_feed = self.price_feeds.get(request_hash);
-            if let Some(price_feed) = price_feed {
-                let price_time = price_feed.timestamp;
-                if super::diff(price_time, now) <= age {
-                    return Some(price_feed);
-                }
-            }
-            None
+        pub fn propose(&mut self, action: AdminAction) -> Result<u32, Error> {
+            self.ensure_owner()?;
+            let proposer = self.env().caller();
+            let mut config = self.config();
+            let proposal_id = config.next_proposal_id;
+            config.next_proposal_id = config.next_proposal_id.wrapping_add(1);
+            self.config.set(&config);
+            let proposal = Proposal {
+                action: action.clone(),
+                proposer,
+                approvals: vec![proposer],
+                expires_at: self
+                    .env()
+                    .block_timestamp()
+                    .saturating_add(config.proposal_ttl),
+            };
+            self.env().emit_event(ProposalCreated {
+                proposal_id,
+                proposer,
+                action,
+            });
+            self.execute_if_approved(proposal_id, proposal)?;
+            Ok(proposal_id)
         }
 
+        /// Approves a proposal and executes it once it reaches its quorum.
         #[ink(message)]
-        fn update_signers_threshold(&mut self, new_threshold: u8) -> Result<(), Error> {
+        pub fn approve(&mut self, proposal_id: u32) -> Result<(), Error> {
             self.ensure_owner()?;
-            self.valid_signers_threshold = new_threshold;
-            self.env()
-                .emit_event(SignersThresholdUpdate { new_threshold });
-            Ok(())
+            let approver = self.env().caller();
+            let mut proposal = self
+                .proposals
+                .get(proposal_id)
+                .ok_or(Error::ProposalNotFound)?;
+            if proposal.expires_at < self.env().block_timestamp() {
+                return Err(Error::ProposalExpired);
+            }
+            if proposal.approvals.contains(&approver) {
+                return Err(Error::AlreadyApproved);
+            }
+            proposal.approvals.push(approver);
+            self.env().emit_event(ProposalApproved {
+                proposal_id,
+                approver,
+            });
+            self.execute_if_approved(proposal_id, proposal)
         }
 
+        /// Cancels a proposal, callable by its proposer, or by any owner once it expired.
         #[ink(message)]
-        fn update_sources_threshold(&mut self, new_threshold: u8) -> Result<(), Error> {
+        pub fn cancel(&mut self, proposal_id: u32) -> Result<(), Error> {
             self.ensure_owner()?;
-            self.valid_sources_threshold = new_threshold;
-            self.env()
-                .emit_event(SourcesThresholdUpdate { new_threshold });
+            let proposal = self
+                .proposals
+                .get(proposal_id)
+                .ok_or(Error::ProposalNotFound)?;
+            if proposal.proposer != self.env().caller()
+                && proposal.expires_at >= self.env().block_timestamp()
+            {
+                return Err(Error::NotProposer);
+            }
+            self.proposals.remove(proposal_id);
+            self.env().emit_event(ProposalCancelled { proposal_id });
             Ok(())
         }
 
         #[ink(message)]
-        fn update_valid_time_period(&mut self, new_time_period: u64) -> Result<(), Error> {
-            self.ensure_owner()?;
-            self.valid_time_period = new_time_period;
-            self.env()
-                .emit_event(ValidTimePeriodUpdate { new_time_period });
-            Ok(())
+        pub fn get_proposal(&self, proposal_id: u32) -> Option<Proposal> {
+            self.proposals.get(proposal_id)
         }
 
+        /// The number of owner approvals an admin action needs, and the lower one of defensive actions.
         #[ink(message)]
-        fn add_trusted_signer(&mut self, new_trusted_signer: AccountId) -> Result<(), Error> {
-            self.ensure_owner()?;
-            self.trusted_signers.insert(new_trusted_signer, &true);
-            self.env()
-                .emit_event(TrustedSignerAdded { new_trusted_signer });
-            Ok(())
+        pub fn get_quorum(&self) -> (u8, u8) {
+            let config = self.config();
+            (config.quorum, config.defensive_quorum)
         }
 
         #[ink(message)]
-        fn remove_trusted_signer(&mut self, trusted_signer: AccountId) -> Result<(), Error> {
-            self.ensure_owner()?;
-            let maybe_removed_signer = self.trusted_signers.take(trusted_signer);
-            if maybe_removed_signer.is_some() {
-                self.env()
-                    .emit_event(TrustedSignerRemoved { trusted_signer });
-            }
-            Ok(())
+        pub fn get_proposal_ttl(&self) -> u64 {
+            self.config().proposal_ttl
         }
 
+        /// Replaces the code of the contract, keeping its storage.
+        ///
+        /// If the new code bumps [`STORAGE_VERSION`], `migrate` has to be called right after.
         #[ink(message)]
-        fn add_certificate_to_trust_store(
-            &mut self,
-            new_certificate: Certificate,
-        ) -> Result<(), Error> {
-            self.ensure_owner()?;
-            self.certificate_trust_store.insert(new_certificate, &true);
-            self.env().emit_event(CertificateAdded { new_certificate });
-            Ok(())
+        pub fn upgrade(&mut self, code_hash: Hash) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::Upgrade(code_hash))
         }
 
+        /// Migrates the storage written by a previous code version to the current layout.
+        ///
+        /// Supports the deployments of the storage versions 1 and 2, see [`STORAGE_VERSION`].
+        ///
+        /// Price entries and owners can't be iterated, so the feeds to migrate are passed by the
+        /// caller, in as many batches as needed, and so are the owners, which are only counted when
+        /// the configuration of the first storage version is migrated. Entries already in the current
+        /// layout are left as they are.
         #[ink(message)]
-        fn remove_certificate_to_trust_store(
+        pub fn migrate(
             &mut self,
-            certificate: Certificate,
+            request_hashes: Vec<RequestHash>,
+            owners: Vec<AccountId>,
         ) -> Result<(), Error> {
             self.ensure_owner()?;
-            let maybe_removed_cert = self.certificate_trust_store.take(certificate);
-            if maybe_removed_cert.is_some() {
-                self.env().emit_event(CertificateRemoved { certificate });
+            if self.config.get().is_none() {
+                let mut counted_owners = BTreeSet::new();
+                counted_owners.insert(self.env().caller());
+                for owner in owners {
+                    if self.owners.contains(owner) {
+                        counted_owners.insert(owner);
+                    }
+                }
+                self.config.set(&Config {
+                    owners_count: counted_owners.len() as u32,
+                    ..Config::default()
+                });
+            }
+            for request_hash in request_hashes {
+                let key = (self.price_feeds.key(), request_hash);
+                if ink::env::get_contract_storage::<_, PriceEntry>(&key).is_ok() {
+                    continue;
+                }
+                let price_entry_v1 = ink::env::get_contract_storage::<_, PriceEntryV1>(&key)
+                    .map_err(|_| Error::MigrationFailed)?;
+                if let Some(price_entry_v1) = price_entry_v1 {
+                    self.price_feeds
+                        .insert(request_hash, &PriceEntry::from(price_entry_v1));
+                }
+            }
+            let storage_version = self.get_storage_version();
+            if storage_version < STORAGE_VERSION {
+                self.env().emit_event(Migrated {
+                    from_version: storage_version,
+                    to_version: STORAGE_VERSION,
+                });
+                self.storage_version.set(&STORAGE_VERSION);
             }
             Ok(())
         }
-    }
 
-    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
-    /// module and test functions are marked with a `#[test]` attribute.
-    /// The below code is technically just normal Rust code.
-    #[cfg(test)]
-    mod tests {
-        /// Imports all the definitions from the outer scope so we can use them here.
-        use super::*;
-        use hex_literal::hex;
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version.get().unwrap_or(1)
+        }
 
-        /// We test if the default constructor does its job.
-        #[ink::test]
-        fn default_works() {
-            let acelon_oracle = AcelonOracle::default();
-            assert_eq!(acelon_oracle.get_valid_time_period(), 3_600_000);
+        /// Returns the stored configuration, or the default one until a first version deployment is
+        /// migrated.
+        fn config(&self) -> Config {
+            self.config.get().unwrap_or_default()
         }
 
-        /// We test a simple use case of our contract.
-        #[ink::test]
-        fn test_update_price_feed() {
-            let oracle_1: AccountId = blake2_256(
-                hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020")
-                    .as_slice(),
-            )
-            .into();
-            let mut acelon_oracle = AcelonOracle::new(
-                vec![],
-                vec![oracle_1],
-                vec![
-                    hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e"),
-                    hex!("4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384"),
-                    hex!("b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80"),
-                ],
-                1,
-                1,
-                0,
-            );
-            let result = acelon_oracle.update_price_feeds(
-                vec![hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc").to_vec()], 
-                vec![vec![hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b")]],
-            );
-            assert_eq!(result, Ok(()));
+        fn update_config(&mut self, update: impl FnOnce(&mut Config)) {
+            let mut config = self.config();
+            update(&mut config);
+            self.config.set(&config);
         }
 
-        #[inline(always)]
-        fn blake2<const N: usize>(data: &[u8]) -> [u8; N] {
-            blake2b_simd::Params::new()
-                .hash_length(N)
-                .hash(data)
-                .as_bytes()
-                .try_into()
-                .expect("slice is always the necessary length")
+        /// Returns the timestamp after which a proposed owner can no longer accept.
+        ///
+        /// The proposals of the first storage version have no expiry and are expired.
+        fn proposal_expiry(&self, account: AccountId) -> Option<u64> {
+            let key = (self.proposed_owners.key(), account);
+            ink::env::get_contract_storage::<_, u64>(&key).unwrap_or(Some(0))
         }
 
-        pub fn blake2_256(data: &[u8]) -> [u8; 32] {
-            blake2(data)
+        /// Stores a proposal, or executes and removes it if enough of its approvers are still owners.
+        fn execute_if_approved(
+            &mut self,
+            proposal_id: u32,
+            proposal: Proposal,
+        ) -> Result<(), Error> {
+            let approvals = proposal
+                .approvals
+                .iter()
+                .filter(|approver| self.owners.contains(*approver))
+                .count();
+            if approvals < self.required_quorum(&proposal.action) as usize {
+                self.proposals.insert(proposal_id, &proposal);
+                return Ok(());
+            }
+            self.proposals.remove(proposal_id);
+            self.execute(proposal.action)?;
+            self.env().emit_event(ProposalExecuted { proposal_id });
+            Ok(())
+        }
+
+        /// Executes an action called directly by an owner, only allowed while its quorum is a single owner.
+        fn execute_as_owner(&mut self, action: AdminAction) -> Result<(), Error> {
+            self.ensure_owner()?;
+            if self.required_quorum(&action) > 1 {
+                return Err(Error::QuorumRequired);
+            }
+            self.execute(action)
+        }
+
+        fn required_quorum(&self, action: &AdminAction) -> u8 {
+            let config = self.config();
+            if action.is_defensive() {
+                config.defensive_quorum
+            } else {
+                config.quorum
+            }
+        }
+
+        fn execute(&mut self, action: AdminAction) -> Result<(), Error> {
+            match action {
+                AdminAction::UpdateSignersThreshold(new_threshold) => {
+                    self.valid_signers_threshold = new_threshold;
+                    self.env()
+                        .emit_event(SignersThresholdUpdate { new_threshold });
+                }
+                AdminAction::UpdateSourcesThreshold(new_threshold) => {
+                    self.valid_sources_threshold = new_threshold;
+                    self.env()
+                        .emit_event(SourcesThresholdUpdate { new_threshold });
+                }
+                AdminAction::UpdateValidTimePeriod(new_time_period) => {
+                    self.valid_time_period = new_time_period;
+                    self.env()
+                        .emit_event(ValidTimePeriodUpdate { new_time_period });
+                }
+                AdminAction::UpdateMaxFutureDrift(max_future_drift) => {
+                    self.update_config(|config| config.max_future_drift = max_future_drift);
+                    self.env()
+                        .emit_event(MaxFutureDriftUpdate { max_future_drift });
+                }
+                AdminAction::UpdateMaxSubmissionAge(max_submission_age) => {
+                    self.update_config(|config| config.max_submission_age = max_submission_age);
+                    self.env()
+                        .emit_event(MaxSubmissionAgeUpdate { max_submission_age });
+                }
+                AdminAction::UpdateRegisteredFeedsOnly(registered_feeds_only) => {
+                    self.update_config(|config| {
+                        config.registered_feeds_only = registered_feeds_only
+                    });
+                    self.env().emit_event(RegisteredFeedsOnlyUpdate {
+                        registered_feeds_only,
+                    });
+                }
+                AdminAction::UpdateRequireSigningDomain(require_signing_domain) => {
+                    self.update_config(|config| {
+                        config.require_signing_domain = require_signing_domain
+                    });
+                    self.env().emit_event(RequireSigningDomainUpdate {
+                        require_signing_domain,
+                    });
+                }
+                AdminAction::RegisterFeed(request_hash) => {
+                    if self.registered_feeds.contains(request_hash) {
+                        return Err(Error::FeedAlreadyRegistered);
+                    }
+                    self.registered_feeds.insert(request_hash, &());
+                    self.env().emit_event(FeedRegistered { request_hash });
+                }
+                AdminAction::DeregisterFeed(request_hash) => {
+                    if self.registered_feeds.take(request_hash).is_none() {
+                        return Err(Error::FeedNotRegistered);
+                    }
+                    self.env().emit_event(FeedDeregistered { request_hash });
+                }
+                AdminAction::SetFeedConfig(request_hash, config) => {
+                    self.feed_configs.insert(request_hash, &config);
+                    self.env().emit_event(FeedConfigSet {
+                        request_hash,
+                        config,
+                    });
+                }
+                AdminAction::ClearFeedConfig(request_hash) => {
+                    self.feed_configs.remove(request_hash);
+                    self.env().emit_event(FeedConfigCleared { request_hash });
+                }
+                AdminAction::AddTrustedSigner(new_trusted_signer) => {
+                    self.trusted_signers.insert(new_trusted_signer, &true);
+                    self.env()
+                        .emit_event(TrustedSignerAdded { new_trusted_signer });
+                }
+                AdminAction::RemoveTrustedSigner(trusted_signer) => {
+                    let maybe_removed_signer = self.trusted_signers.take(trusted_signer);
+                    if maybe_removed_signer.is_some() {
+                        self.env()
+                            .emit_event(TrustedSignerRemoved { trusted_signer });
+                    }
+                }
+                AdminAction::AddCertificate(new_certificate) => {
+                    self.certificate_trust_store.insert(new_certificate, &true);
+                    self.env().emit_event(CertificateAdded { new_certificate });
+                }
+                AdminAction::RemoveCertificate(certificate) => {
+                    let maybe_removed_cert = self.certificate_trust_store.take(certificate);
+                    if maybe_removed_cert.is_some() {
+                        self.env().emit_event(CertificateRemoved { certificate });
+                    }
+                }
+                AdminAction::ProposeOwner(new_owner) => {
+                    let expires_at = self
+                        .env()
+                        .block_timestamp()
+                        .saturating_add(self.config().proposal_ttl);
+                    self.proposed_owners.insert(new_owner, &expires_at);
+                    self.env().emit_event(OwnerProposed {
+                        proposed_owner: new_owner,
+                    });
+                }
+                AdminAction::CancelOwnerProposal(proposed_owner) => {
+                    // the proposals of the first storage version have no expiry, so they are not decoded
+                    if self.proposed_owners.contains(proposed_owner) {
+                        self.proposed_owners.remove(proposed_owner);
+                        self.env()
+                            .emit_event(OwnerProposalCancelled { proposed_owner });
+                    }
+                }
+                AdminAction::RemoveOwner(owner) => {
+                    if self.owners.contains(owner) {
+                        let mut config = self.config();
+                        if config.owners_count <= 1 {
+                            return Err(Error::LastOwner);
+                        }
+                        if config.owners_count - 1 < config.quorum as u32 {
+                            return Err(Error::InvalidQuorum);
+                        }
+                        self.owners.remove(owner);
+                        config.owners_count -= 1;
+                        self.config.set(&config);
+                        self.env().emit_event(OwnerRemoved { owner })
+                    }
+                }
+                AdminAction::AddGuardian(guardian) => {
+                    self.guardians.insert(guardian, &());
+                    self.env().emit_event(GuardianAdded { guardian });
+                }
+                AdminAction::RemoveGuardian(guardian) => {
+                    let maybe_removed_guardian = self.guardians.take(guardian);
+                    if maybe_removed_guardian.is_some() {
+                        self.env().emit_event(GuardianRemoved { guardian });
+                    }
+                }
+                AdminAction::Unpause(scope) => {
+                    match &scope {
+                        PauseScope::All => self.update_config(|config| config.paused = false),
+                        PauseScope::Feeds(request_hashes) => {
+                            for request_hash in request_hashes {
+                                self.paused_feeds.remove(request_hash);
+                            }
+                        }
+                    }
+                    self.env().emit_event(Unpaused { scope });
+                }
+                AdminAction::UpdateQuorum {
+                    quorum,
+                    defensive_quorum,
+                } => {
+                    let mut config = self.config();
+                    if quorum == 0
+                        || defensive_quorum == 0
+                        || defensive_quorum > quorum
+                        || quorum as u32 > config.owners_count
+                    {
+                        return Err(Error::InvalidQuorum);
+                    }
+                    config.quorum = quorum;
+                    config.defensive_quorum = defensive_quorum;
+                    self.config.set(&config);
+                    self.env().emit_event(QuorumUpdate {
+                        quorum,
+                        defensive_quorum,
+                    });
+                }
+                AdminAction::UpdateProposalTtl(proposal_ttl) => {
+                    self.update_config(|config| config.proposal_ttl = proposal_ttl);
+                    self.env().emit_event(ProposalTtlUpdate { proposal_ttl });
+                }
+                AdminAction::Upgrade(code_hash) => {
+                    self.env()
+                        .set_code_hash(&code_hash)
+                        .map_err(|_| Error::UpgradeFailed)?;
+                    self.env().emit_event(Upgraded { code_hash });
+                }
+            }
+            Ok(())
+        }
+
+        fn ensure_owner_or_guardian(&self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            if !self.owners.contains(caller) && !self.guardians.contains(caller) {
+                return Err(Error::NotGuardian);
+            }
+            Ok(())
+        }
+
+        fn ensure_owner(&self) -> Result<(), Error> {
+            let caller = self.env().caller();
+            let maybe_owner = self.owners.get(caller);
+            if maybe_owner.is_none() {
+                return Err(Error::NotOwner);
+            }
+            Ok(())
+        }
+
+        /// Checks the signatures and the certificates of a single update and returns its decoded payload.
+        fn verify_price_update(
+            &self,
+            data: &[u8],
+            signatures_to_check: &[Signature],
+        ) -> Result<VersionedPricePayload, Error> {
+            let config = self.config();
+            if config.paused {
+                return Err(Error::Paused);
+            }
+
+            // the signed data has to be bound to this contract, unless legacy payloads are accepted
+            let (signing_domain, payload_data) = SigningDomain::split(data);
+            match signing_domain {
+                Some(signing_domain) if signing_domain != self.get_signing_domain() => {
+                    return Err(Error::InvalidSigningDomain);
+                }
+                None if config.require_signing_domain => return Err(Error::InvalidSigningDomain),
+                _ => {}
+            }
+
+            // the payload is decoded upfront to apply the configuration of its feed,
+            // but it is only rejected as invalid once the signatures are checked
+            let decoded_payload = VersionedPricePayload::decode_from(payload_data);
+            let feed_config = decoded_payload
+                .as_ref()
+                .ok()
+                .and_then(|price_payload| self.feed_configs.get(price_payload.request_hash()))
+                .unwrap_or_default();
+
+            // 1. check the signatures, a signer is counted once however many times it signed
+            let mut valid_signers = BTreeSet::<AccountId>::new();
+            let message_hash = self.env().hash_bytes::<Blake2x256>(data);
+            let signers_threshold = feed_config
+                .valid_signers_threshold
+                .unwrap_or(self.valid_signers_threshold)
+                as usize;
+            for signature in signatures_to_check {
+                if let Ok(signer_pk) = self.env().ecdsa_recover(signature, &message_hash) {
+                    let signer_account_id: AccountId =
+                        self.env().hash_bytes::<Blake2x256>(&signer_pk).into();
+                    let is_trusted = self.trusted_signers.get(signer_account_id).unwrap_or(false);
+                    if is_trusted {
+                        valid_signers.insert(signer_account_id);
+                        if valid_signers.len() >= signers_threshold {
+                            break;
+                        }
+                    }
+                }
+            }
+            if valid_signers.len() < signers_threshold {
+                return Err(Error::NotEnoughValidSignatures);
+            }
+
+            // 2. decode the data, check its feed is accepted and its timestamp against the block time
+            let price_paylod = decoded_payload
+                .ok()
+                .filter(|price_payload| price_payload.is_consistent())
+                .ok_or(Error::InvalidPayload)?;
+            if config.registered_feeds_only
+                && !self.registered_feeds.contains(price_paylod.request_hash())
+            {
+                return Err(Error::FeedNotRegistered);
+            }
+            if self.paused_feeds.contains(price_paylod.request_hash()) {
+                return Err(Error::Paused);
+            }
+            let now = self.env().block_timestamp();
+            if let Some(max_future_drift) = config.max_future_drift {
+                if price_paylod.timestamp() > now.saturating_add(max_future_drift) {
+                    return Err(Error::TimestampInFuture);
+                }
+            }
+            if let Some(max_submission_age) = config.max_submission_age {
+                if now.saturating_sub(price_paylod.timestamp()) > max_submission_age {
+                    return Err(Error::TimestampTooOld);
+                }
+            }
+            if let Some(valid_until) = price_paylod.valid_until() {
+                if now > valid_until {
+                    return Err(Error::PayloadExpired);
+                }
+            }
+
+            // 3. check certificates, a certificate is counted once however many times it is listed
+            let mut valid_sources = BTreeSet::<&Certificate>::new();
+            let sources_threshold = feed_config
+                .valid_sources_threshold
+                .unwrap_or(self.valid_sources_threshold)
+                as usize;
+            for certificate in price_paylod.certificates() {
+                let is_valid_certificate = self
+                    .certificate_trust_store
+                    .get(certificate)
+                    .unwrap_or(false);
+                if is_valid_certificate {
+                    valid_sources.insert(certificate);
+                    if valid_sources.len() >= sources_threshold {
+                        break;
+                    }
+                }
+            }
+            if valid_sources.len() < sources_threshold {
+                return Err(Error::NotEnoughValidSources);
+            }
+
+            Ok(price_paylod)
+        }
+
+        /// Stores the price of a verified payload if it is more recent than the current one.
+        ///
+        /// Payloads with the same timestamp are ordered by their sequence number, V1 payloads having none.
+        /// Returns true if the price was stored.
+        fn set_price(&mut self, price_paylod: VersionedPricePayload) -> bool {
+            // 4. set the new price
+            let request_hash = price_paylod.request_hash();
+            let is_more_recent = self
+                .price_feeds
+                .get(request_hash)
+                .map(|curret_price| {
+                    (curret_price.timestamp, curret_price.sequence)
+                        < (price_paylod.timestamp(), price_paylod.sequence())
+                })
+                .unwrap_or(true);
+            if is_more_recent {
+                let price_entry = price_paylod.into_price_entry();
+                self.price_feeds.insert(request_hash, &price_entry);
+                self.env().emit_event(PriceFeedUpdate {
+                    request_hash,
+                    price_entry,
+                });
+            }
+            is_more_recent
+        }
+    }
+
+    impl PriceOracle for AcelonOracle {
+        #[ink(message)]
+        fn update_price_feeds(
+            &mut self,
+            update_data: Vec<Vec<u8>>,
+            signatures: Vec<Vec<Signature>>,
+        ) -> Result<(), Error> {
+            if signatures.len() < update_data.len() {
+                return Err(Error::NotEnoughValidSignatures);
+            }
+            for (i, data) in update_data.into_iter().enumerate() {
+                let price_payload = self.verify_price_update(&data, &signatures[i])?;
+                self.set_price(price_payload);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn try_update_price_feeds(
+            &mut self,
+            update_data: Vec<Vec<u8>>,
+            signatures: Vec<Vec<Signature>>,
+        ) -> Vec<PriceUpdateResult> {
+            let mut results = Vec::with_capacity(update_data.len());
+            for (i, data) in update_data.into_iter().enumerate() {
+                let signatures_to_check = signatures.get(i).map(Vec::as_slice).unwrap_or_default();
+                let result = match self.verify_price_update(&data, signatures_to_check) {
+                    Ok(price_payload) => {
+                        if self.set_price(price_payload) {
+                            PriceUpdateResult::Updated
+                        } else {
+                            PriceUpdateResult::NotMoreRecent
+                        }
+                    }
+                    Err(reason) => {
+                        self.env().emit_event(PriceFeedRejected {
+                            index: i as u32,
+                            request_hash: VersionedPricePayload::decode_from(
+                                SigningDomain::split(&data).1,
+                            )
+                            .ok()
+                            .map(|price_payload| price_payload.request_hash()),
+                            reason,
+                        });
+                        PriceUpdateResult::Rejected(reason)
+                    }
+                };
+                results.push(result);
+            }
+            results
+        }
+
+        #[ink(message)]
+        fn price_feed_exists(&self, request_hash: RequestHash) -> bool {
+            self.price_feeds
+                .get(request_hash)
+                .map(|value| value.timestamp > 0)
+                .unwrap_or(false)
+        }
+
+        #[ink(message)]
+        fn get_valid_time_period(&self) -> u64 {
+            self.valid_time_period
+        }
+
+        #[ink(message)]
+        fn get_max_future_drift(&self) -> Option<u64> {
+            self.config().max_future_drift
+        }
+
+        #[ink(message)]
+        fn get_max_submission_age(&self) -> Option<u64> {
+            self.config().max_submission_age
+        }
+
+        #[ink(message)]
+        fn get_feed_config(&self, request_hash: RequestHash) -> Option<FeedConfig> {
+            self.feed_configs.get(request_hash)
+        }
+
+        #[ink(message)]
+        fn get_registered_feeds_only(&self) -> bool {
+            self.config().registered_feeds_only
+        }
+
+        #[ink(message)]
+        fn get_require_signing_domain(&self) -> bool {
+            self.config().require_signing_domain
+        }
+
+        #[ink(message)]
+        fn get_signing_domain(&self) -> SigningDomain {
+            SigningDomain::new(*self.env().account_id().as_ref())
+        }
+
+        #[ink(message)]
+        fn is_feed_registered(&self, request_hash: RequestHash) -> bool {
+            self.registered_feeds.contains(request_hash)
+        }
+
+        #[ink(message)]
+        fn get_price(&self, request_hash: RequestHash) -> Result<PriceEntry, Error> {
+            let valid_time_period = self
+                .feed_configs
+                .get(request_hash)
+                .and_then(|config| config.valid_time_period)
+                .unwrap_or(self.valid_time_period);
+            self.get_price_no_holder_than(request_hash, valid_time_period)
+        }
+
+        #[ink(message)]
+        fn get_price_no_holder_than(
+            &self,
+            request_hash: RequestHash,
+            age: u64,
+        ) -> Result<PriceEntry, Error> {
+            if self.is_feed_paused(request_hash) {
+                return Err(Error::Paused);
+            }
+            let now = self.env().block_timestamp();
+            let price_feed = self
+                .price_feeds
+                .get(request_hash)
+                .ok_or(Error::PriceNotFound)?;
+            let price_time = price_feed.timestamp;
+            if now.saturating_sub(price_time) > age || price_feed.is_expired(now) {
+                return Err(Error::PriceStale);
+            }
+            Ok(price_feed)
+        }
+
+        #[ink(message)]
+        fn get_prices(&self, request_hashes: Vec<RequestHash>) -> Vec<Result<PriceEntry, Error>> {
+            request_hashes
+                .into_iter()
+                .map(|request_hash| self.get_price(request_hash))
+                .collect()
+        }
+
+        #[ink(message)]
+        fn get_prices_no_older_than(
+            &self,
+            request_hashes: Vec<RequestHash>,
+            age: u64,
+        ) -> Vec<Result<PriceEntry, Error>> {
+            request_hashes
+                .into_iter()
+                .map(|request_hash| self.get_price_no_holder_than(request_hash, age))
+                .collect()
+        }
+
+        #[ink(message)]
+        fn update_signers_threshold(&mut self, new_threshold: u8) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateSignersThreshold(new_threshold))
+        }
+
+        #[ink(message)]
+        fn update_sources_threshold(&mut self, new_threshold: u8) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateSourcesThreshold(new_threshold))
+        }
+
+        #[ink(message)]
+        fn update_valid_time_period(&mut self, new_time_period: u64) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateValidTimePeriod(new_time_period))
+        }
+
+        #[ink(message)]
+        fn update_max_future_drift(&mut self, max_future_drift: Option<u64>) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateMaxFutureDrift(max_future_drift))
+        }
+
+        #[ink(message)]
+        fn update_max_submission_age(
+            &mut self,
+            max_submission_age: Option<u64>,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateMaxSubmissionAge(max_submission_age))
+        }
+
+        #[ink(message)]
+        fn update_registered_feeds_only(
+            &mut self,
+            registered_feeds_only: bool,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateRegisteredFeedsOnly(
+                registered_feeds_only,
+            ))
+        }
+
+        #[ink(message)]
+        fn update_require_signing_domain(
+            &mut self,
+            require_signing_domain: bool,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::UpdateRequireSigningDomain(
+                require_signing_domain,
+            ))
+        }
+
+        #[ink(message)]
+        fn register_feed(&mut self, request_hash: RequestHash) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::RegisterFeed(request_hash))
+        }
+
+        #[ink(message)]
+        fn deregister_feed(&mut self, request_hash: RequestHash) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::DeregisterFeed(request_hash))
+        }
+
+        #[ink(message)]
+        fn set_feed_config(
+            &mut self,
+            request_hash: RequestHash,
+            config: FeedConfig,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::SetFeedConfig(request_hash, config))
+        }
+
+        #[ink(message)]
+        fn clear_feed_config(&mut self, request_hash: RequestHash) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::ClearFeedConfig(request_hash))
+        }
+
+        #[ink(message)]
+        fn add_trusted_signer(&mut self, new_trusted_signer: AccountId) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::AddTrustedSigner(new_trusted_signer))
+        }
+
+        #[ink(message)]
+        fn remove_trusted_signer(&mut self, trusted_signer: AccountId) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::RemoveTrustedSigner(trusted_signer))
+        }
+
+        #[ink(message)]
+        fn add_certificate_to_trust_store(
+            &mut self,
+            new_certificate: Certificate,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::AddCertificate(new_certificate))
+        }
+
+        #[ink(message)]
+        fn remove_certificate_to_trust_store(
+            &mut self,
+            certificate: Certificate,
+        ) -> Result<(), Error> {
+            self.execute_as_owner(AdminAction::RemoveCertificate(certificate))
+        }
+    }
+
+    /// Unit tests in Rust are normally defined within such a `#[cfg(test)]`
+    /// module and test functions are marked with a `#[test]` attribute.
+    /// The below code is technically just normal Rust code.
+    #[cfg(test)]
+    mod tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+        use crate::types::{PricePayload, PricePayloadV2};
+        use hex_literal::hex;
+        use scale::Encode;
+        use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+        const PAYLOAD: [u8; 154] = hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+        const SIGNATURE: Signature = hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b");
+        const PAYLOAD_TIMESTAMP: u64 = 1_728_394_897_587;
+        const REQUEST_HASH: RequestHash =
+            hex!("dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+        const CERTIFICATE: Certificate =
+            hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e");
+
+        /// We test if the default constructor does its job.
+        #[ink::test]
+        fn default_works() {
+            let acelon_oracle = AcelonOracle::default();
+            assert_eq!(acelon_oracle.get_valid_time_period(), 3_600_000);
+            assert!(acelon_oracle.is_owner(owner()));
+            assert_eq!(acelon_oracle.get_owners_count(), 1);
+        }
+
+        /// We test a simple use case of our contract.
+        #[ink::test]
+        fn test_update_price_feed() {
+            let oracle_1: AccountId = blake2_256(
+                hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020")
+                    .as_slice(),
+            )
+            .into();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![],
+                vec![oracle_1],
+                vec![
+                    hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e"),
+                    hex!("4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384"),
+                    hex!("b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80"),
+                ],
+                1,
+                1,
+                0,
+            );
+            let result = acelon_oracle.update_price_feeds(
+                vec![hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc").to_vec()], 
+                vec![vec![hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b")]],
+            );
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn duplicated_signatures_are_counted_once() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![], vec![oracle()], vec![CERTIFICATE], 2, 1, 0);
+            let result = acelon_oracle
+                .update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE, SIGNATURE]]);
+            assert_eq!(result, Err(Error::NotEnoughValidSignatures));
+
+            let other_signer = SecretKey::from_slice(&[1u8; 32]).unwrap();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![],
+                vec![oracle(), account_of(&other_signer)],
+                vec![CERTIFICATE],
+                2,
+                1,
+                0,
+            );
+            let result = acelon_oracle.update_price_feeds(
+                vec![PAYLOAD.to_vec()],
+                vec![vec![SIGNATURE, sign(&other_signer, &PAYLOAD)]],
+            );
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn duplicated_certificates_are_counted_once() {
+            let signer = SecretKey::from_slice(&[1u8; 32]).unwrap();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![],
+                vec![account_of(&signer)],
+                vec![CERTIFICATE],
+                1,
+                2,
+                0,
+            );
+            let payload = PricePayload {
+                prices: vec![1_000],
+                timestamp: PAYLOAD_TIMESTAMP,
+                certificates: vec![CERTIFICATE, CERTIFICATE],
+                request_hash: REQUEST_HASH,
+            }
+            .encode();
+            let result = acelon_oracle
+                .update_price_feeds(vec![payload.clone()], vec![vec![sign(&signer, &payload)]]);
+            assert_eq!(result, Err(Error::NotEnoughValidSources));
+        }
+
+        #[ink::test]
+        fn try_update_price_feeds_applies_valid_updates() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+            let results = acelon_oracle.try_update_price_feeds(
+                vec![vec![1, 2, 3], PAYLOAD.to_vec(), PAYLOAD.to_vec()],
+                vec![vec![SIGNATURE], vec![SIGNATURE], vec![SIGNATURE]],
+            );
+            assert_eq!(
+                results,
+                vec![
+                    PriceUpdateResult::Rejected(Error::NotEnoughValidSignatures),
+                    PriceUpdateResult::Updated,
+                    PriceUpdateResult::NotMoreRecent,
+                ]
+            );
+            assert!(acelon_oracle.price_feed_exists(REQUEST_HASH));
+        }
+
+        #[ink::test]
+        fn payload_timestamp_must_be_admissible() {
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![owner()],
+                vec![oracle()],
+                vec![CERTIFICATE],
+                1,
+                1,
+                3_600_000,
+            );
+            acelon_oracle.update_max_future_drift(Some(5_000)).unwrap();
+            acelon_oracle
+                .update_max_submission_age(Some(60_000))
+                .unwrap();
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP - 5_001,
+            );
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::TimestampInFuture));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP + 60_001,
+            );
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::TimestampTooOld));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP - 5_000,
+            );
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn feed_config_overrides_global_config() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![owner()], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+            let config = FeedConfig {
+                valid_signers_threshold: Some(2),
+                valid_sources_threshold: None,
+                valid_time_period: Some(60_000),
+            };
+            acelon_oracle.set_feed_config(REQUEST_HASH, config).unwrap();
+            assert_eq!(acelon_oracle.get_feed_config(REQUEST_HASH), Some(config));
+
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::NotEnoughValidSignatures));
+
+            acelon_oracle
+                .set_feed_config(
+                    REQUEST_HASH,
+                    FeedConfig {
+                        valid_signers_threshold: Some(1),
+                        ..config
+                    },
+                )
+                .unwrap();
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP + 30_000,
+            );
+            assert!(acelon_oracle.get_price(REQUEST_HASH).is_ok());
+
+            acelon_oracle.clear_feed_config(REQUEST_HASH).unwrap();
+            assert_eq!(acelon_oracle.get_feed_config(REQUEST_HASH), None);
+            assert_eq!(
+                acelon_oracle.get_price(REQUEST_HASH),
+                Err(Error::PriceStale)
+            );
+        }
+
+        #[ink::test]
+        fn registered_feeds_only_rejects_unregistered_feeds() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![owner()], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+            acelon_oracle.update_registered_feeds_only(true).unwrap();
+
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::FeedNotRegistered));
+            let results =
+                acelon_oracle.try_update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(
+                results,
+                vec![PriceUpdateResult::Rejected(Error::FeedNotRegistered)]
+            );
+
+            acelon_oracle.register_feed(REQUEST_HASH).unwrap();
+            assert!(acelon_oracle.is_feed_registered(REQUEST_HASH));
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+        }
+
+        #[ink::test]
+        fn registering_is_rejected_when_nothing_changes() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![owner()], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+
+            assert_eq!(
+                acelon_oracle.deregister_feed(REQUEST_HASH),
+                Err(Error::FeedNotRegistered)
+            );
+            acelon_oracle.register_feed(REQUEST_HASH).unwrap();
+            assert_eq!(
+                acelon_oracle.register_feed(REQUEST_HASH),
+                Err(Error::FeedAlreadyRegistered)
+            );
+            acelon_oracle.deregister_feed(REQUEST_HASH).unwrap();
+            assert!(!acelon_oracle.is_feed_registered(REQUEST_HASH));
+            assert_eq!(
+                acelon_oracle.deregister_feed(REQUEST_HASH),
+                Err(Error::FeedNotRegistered)
+            );
+        }
+
+        #[ink::test]
+        fn updates_are_bound_to_the_contract() {
+            let signer = SecretKey::from_slice(&[1u8; 32]).unwrap();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![owner()],
+                vec![oracle(), account_of(&signer)],
+                vec![CERTIFICATE],
+                1,
+                1,
+                0,
+            );
+            let payload = PricePayload {
+                prices: vec![1_000],
+                timestamp: PAYLOAD_TIMESTAMP,
+                certificates: vec![CERTIFICATE],
+                request_hash: REQUEST_HASH,
+            }
+            .encode();
+            let signing_domain = acelon_oracle.get_signing_domain();
+            assert_eq!(
+                signing_domain,
+                SigningDomain::new(
+                    *ink::env::test::callee::<ink::env::DefaultEnvironment>().as_ref()
+                )
+            );
+
+            // any domain but the contract's own, the default callee of the test environment
+            let other_domain = SigningDomain::new([0xaa; 32]);
+            assert_ne!(acelon_oracle.get_signing_domain(), other_domain);
+            let other_domain = other_domain.bind(&payload);
+            let result = acelon_oracle.update_price_feeds(
+                vec![other_domain.clone()],
+                vec![vec![sign(&signer, &other_domain)]],
+            );
+            assert_eq!(result, Err(Error::InvalidSigningDomain));
+
+            // legacy payloads are accepted until a signing domain is required
+            acelon_oracle.update_require_signing_domain(true).unwrap();
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::InvalidSigningDomain));
+
+            let data = signing_domain.bind(&payload);
+            let result = acelon_oracle
+                .update_price_feeds(vec![data.clone()], vec![vec![sign(&signer, &data)]]);
+            assert_eq!(result, Ok(()));
+            assert!(acelon_oracle.price_feed_exists(REQUEST_HASH));
+        }
+
+        #[ink::test]
+        fn price_payload_v2_is_accepted() {
+            let signer = SecretKey::from_slice(&[1u8; 32]).unwrap();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![owner()],
+                vec![oracle(), account_of(&signer)],
+                vec![CERTIFICATE],
+                1,
+                1,
+                60_000,
+            );
+            let payload_v2 = |valid_until: u64, sequence: u64| PricePayloadV2 {
+                prices: vec![1_000],
+                decimals: vec![8],
+                confidences: vec![10],
+                timestamp: PAYLOAD_TIMESTAMP,
+                valid_until,
+                sequence,
+                certificates: vec![CERTIFICATE],
+                request_hash: REQUEST_HASH,
+            };
+            let mut update = |payload: VersionedPricePayload| {
+                let data = payload.encode();
+                acelon_oracle
+                    .update_price_feeds(vec![data.clone()], vec![vec![sign(&signer, &data)]])
+            };
+
+            // a V1 payload is decoded as before and stores no V2 fields
+            assert_eq!(
+                update(VersionedPricePayload::V1(PricePayload {
+                    prices: vec![999],
+                    timestamp: PAYLOAD_TIMESTAMP,
+                    certificates: vec![CERTIFICATE],
+                    request_hash: REQUEST_HASH,
+                })),
+                Ok(())
+            );
+
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP + 2_000,
+            );
+            let result = update(VersionedPricePayload::V2(payload_v2(
+                PAYLOAD_TIMESTAMP + 1_000,
+                0,
+            )));
+            assert_eq!(result, Err(Error::PayloadExpired));
+
+            // the decimals and the confidences must match the prices
+            let mut inconsistent = payload_v2(PAYLOAD_TIMESTAMP + 5_000, 0);
+            inconsistent.decimals = vec![];
+            let result = update(VersionedPricePayload::V2(inconsistent));
+            assert_eq!(result, Err(Error::InvalidPayload));
+
+            // with the same timestamp, a V2 payload is more recent than a V1 one
+            let result = update(VersionedPricePayload::V2(payload_v2(
+                PAYLOAD_TIMESTAMP + 5_000,
+                1,
+            )));
+            assert_eq!(result, Ok(()));
+            let price = acelon_oracle.get_price(REQUEST_HASH).unwrap();
+            assert_eq!(price.prices, vec![1_000]);
+            assert_eq!(price.decimals, vec![8]);
+            assert_eq!(price.confidences, vec![10]);
+            assert_eq!(price.valid_until, Some(PAYLOAD_TIMESTAMP + 5_000));
+            assert_eq!(price.sequence, Some(1));
+
+            // the price is stale past its expiry, whatever the valid time period
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP + 5_001,
+            );
+            assert_eq!(
+                acelon_oracle.get_price(REQUEST_HASH),
+                Err(Error::PriceStale)
+            );
+        }
+
+        #[ink::test]
+        fn get_prices_tells_why_a_price_is_missing() {
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![owner()],
+                vec![oracle()],
+                vec![CERTIFICATE],
+                1,
+                1,
+                u64::MAX,
+            );
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                PAYLOAD_TIMESTAMP + 10,
+            );
+
+            let prices = acelon_oracle.get_prices(vec![REQUEST_HASH, [0; 32]]);
+            assert_eq!(prices.len(), 2);
+            assert_eq!(
+                prices[0].as_ref().map(|price| price.timestamp),
+                Ok(PAYLOAD_TIMESTAMP)
+            );
+            assert_eq!(prices[1], Err(Error::PriceNotFound));
+
+            let prices = acelon_oracle.get_prices_no_older_than(vec![[0; 32], REQUEST_HASH], 5);
+            assert_eq!(
+                prices,
+                vec![Err(Error::PriceNotFound), Err(Error::PriceStale)]
+            );
+        }
+
+        #[ink::test]
+        fn guardian_can_pause_but_not_unpause() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![owner()],
+                vec![oracle()],
+                vec![CERTIFICATE],
+                1,
+                1,
+                u64::MAX,
+            );
+            acelon_oracle.add_guardian(accounts.bob).unwrap();
+            assert!(acelon_oracle.is_guardian(accounts.bob));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                acelon_oracle.pause(PauseScope::All),
+                Err(Error::NotGuardian)
+            );
+
+            // a paused feed rejects its updates and its price reads
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            acelon_oracle
+                .pause(PauseScope::Feeds(vec![REQUEST_HASH]))
+                .unwrap();
+            assert!(acelon_oracle.is_feed_paused(REQUEST_HASH));
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Err(Error::Paused));
+            assert_eq!(acelon_oracle.get_price(REQUEST_HASH), Err(Error::Paused));
+            assert_eq!(
+                acelon_oracle.unpause(PauseScope::Feeds(vec![REQUEST_HASH])),
+                Err(Error::NotOwner)
+            );
+            assert_eq!(
+                acelon_oracle.update_signers_threshold(0),
+                Err(Error::NotOwner)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle
+                .unpause(PauseScope::Feeds(vec![REQUEST_HASH]))
+                .unwrap();
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+            assert!(acelon_oracle.get_price(REQUEST_HASH).is_ok());
+
+            // a paused oracle rejects every update and every price read
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            acelon_oracle.pause(PauseScope::All).unwrap();
+            let results =
+                acelon_oracle.try_update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(results, vec![PriceUpdateResult::Rejected(Error::Paused)]);
+            assert_eq!(acelon_oracle.get_price(REQUEST_HASH), Err(Error::Paused));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle.unpause(PauseScope::All).unwrap();
+            assert!(!acelon_oracle.is_paused());
+            assert!(acelon_oracle.get_price(REQUEST_HASH).is_ok());
+        }
+
+        #[ink::test]
+        fn admin_actions_need_an_owner_quorum() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut acelon_oracle = AcelonOracle::new(
+                vec![accounts.alice, accounts.bob, accounts.charlie],
+                vec![oracle()],
+                vec![CERTIFICATE],
+                1,
+                1,
+                0,
+            );
+            // with a quorum of one owner, a proposal is executed right away
+            let proposal_id = acelon_oracle
+                .propose(AdminAction::UpdateQuorum {
+                    quorum: 2,
+                    defensive_quorum: 1,
+                })
+                .unwrap();
+            assert_eq!(acelon_oracle.get_quorum(), (2, 1));
+            assert_eq!(acelon_oracle.get_proposal(proposal_id), None);
+
+            assert_eq!(
+                acelon_oracle.update_valid_time_period(60_000),
+                Err(Error::QuorumRequired)
+            );
+            assert_eq!(acelon_oracle.remove_trusted_signer(oracle()), Ok(()));
+
+            let proposal_id = acelon_oracle
+                .propose(AdminAction::UpdateValidTimePeriod(60_000))
+                .unwrap();
+            assert_eq!(
+                acelon_oracle.approve(proposal_id),
+                Err(Error::AlreadyApproved)
+            );
+            assert_eq!(acelon_oracle.get_valid_time_period(), 0);
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            acelon_oracle.approve(proposal_id).unwrap();
+            assert_eq!(acelon_oracle.get_valid_time_period(), 60_000);
+            assert_eq!(
+                acelon_oracle.approve(proposal_id),
+                Err(Error::ProposalNotFound)
+            );
+
+            // only the proposer can cancel a proposal until it expires
+            let proposal_id = acelon_oracle
+                .propose(AdminAction::UpdateValidTimePeriod(0))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(acelon_oracle.cancel(proposal_id), Err(Error::NotProposer));
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                acelon_oracle.get_proposal_ttl() + 1,
+            );
+            assert_eq!(
+                acelon_oracle.approve(proposal_id),
+                Err(Error::ProposalExpired)
+            );
+            acelon_oracle.cancel(proposal_id).unwrap();
+            assert_eq!(acelon_oracle.get_proposal(proposal_id), None);
+            assert_eq!(acelon_oracle.get_valid_time_period(), 60_000);
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.django);
+            assert_eq!(
+                acelon_oracle.propose(AdminAction::UpdateValidTimePeriod(0)),
+                Err(Error::NotOwner)
+            );
+        }
+
+        #[ink::test]
+        fn owners_cannot_be_locked_out() {
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![owner()], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+            assert_eq!(acelon_oracle.remove_owner(owner()), Err(Error::LastOwner));
+
+            // a proposal can be cancelled, or expire, before it is accepted
+            acelon_oracle.propose_owner(accounts.bob).unwrap();
+            assert!(acelon_oracle.is_proposed_owner(accounts.bob));
+            acelon_oracle.cancel_owner_proposal(accounts.bob).unwrap();
+            assert!(!acelon_oracle.is_proposed_owner(accounts.bob));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(acelon_oracle.accept_owner(), Err(Error::NotProposedOwner));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle.propose_owner(accounts.bob).unwrap();
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(
+                acelon_oracle.get_proposal_ttl() + 1,
+            );
+            assert!(!acelon_oracle.is_proposed_owner(accounts.bob));
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            assert_eq!(
+                acelon_oracle.accept_owner(),
+                Err(Error::OwnerProposalExpired)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle.propose_owner(accounts.bob).unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            acelon_oracle.accept_owner().unwrap();
+            assert!(acelon_oracle.is_owner(accounts.bob));
+            assert_eq!(acelon_oracle.get_owners_count(), 2);
+
+            // the owners left must be able to reach the quorum
+            acelon_oracle
+                .propose(AdminAction::UpdateQuorum {
+                    quorum: 2,
+                    defensive_quorum: 1,
+                })
+                .unwrap();
+            let proposal_id = acelon_oracle
+                .propose(AdminAction::RemoveOwner(owner()))
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            assert_eq!(
+                acelon_oracle.approve(proposal_id),
+                Err(Error::InvalidQuorum)
+            );
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.bob);
+            let proposal_id = acelon_oracle
+                .propose(AdminAction::UpdateQuorum {
+                    quorum: 1,
+                    defensive_quorum: 1,
+                })
+                .unwrap();
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle.approve(proposal_id).unwrap();
+            acelon_oracle.remove_owner(owner()).unwrap();
+            assert!(!acelon_oracle.is_owner(owner()));
+            assert_eq!(acelon_oracle.get_owners_count(), 1);
+        }
+
+        #[ink::test]
+        fn migrate_moves_price_entries_to_the_current_layout() {
+            let mut acelon_oracle =
+                AcelonOracle::new(vec![owner()], vec![oracle()], vec![CERTIFICATE], 1, 1, 0);
+            // the storage left by a version 1 code
+            let accounts = ink::env::test::default_accounts::<ink::env::DefaultEnvironment>();
+            ink::env::clear_contract_storage(&acelon_oracle.config.key());
+            ink::env::clear_contract_storage(&acelon_oracle.storage_version.key());
+            ink::env::set_contract_storage(&(acelon_oracle.owners.key(), accounts.bob), &());
+            ink::env::set_contract_storage(
+                &(acelon_oracle.proposed_owners.key(), accounts.eve),
+                &(),
+            );
+            ink::env::set_contract_storage(
+                &(acelon_oracle.price_feeds.key(), REQUEST_HASH),
+                &PriceEntryV1 {
+                    timestamp: PAYLOAD_TIMESTAMP - 1,
+                    prices: vec![1_000],
+                },
+            );
+
+            assert_eq!(acelon_oracle.get_storage_version(), 1);
+            // the pending owner proposals have no expiry, they are expired
+            ink::env::test::set_block_timestamp::<ink::env::DefaultEnvironment>(1);
+            assert!(!acelon_oracle.is_proposed_owner(accounts.eve));
+
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(accounts.charlie);
+            assert_eq!(
+                acelon_oracle.migrate(vec![REQUEST_HASH], vec![]),
+                Err(Error::NotOwner)
+            );
+            ink::env::test::set_caller::<ink::env::DefaultEnvironment>(owner());
+            acelon_oracle
+                .migrate(vec![REQUEST_HASH], vec![accounts.bob, accounts.charlie])
+                .unwrap();
+            assert_eq!(acelon_oracle.get_storage_version(), STORAGE_VERSION);
+            // the caller and the listed accounts which are owners are counted
+            assert_eq!(acelon_oracle.get_owners_count(), 2);
+            assert_eq!(acelon_oracle.get_quorum(), (1, 1));
+            assert_eq!(acelon_oracle.get_max_future_drift(), None);
+            let price = acelon_oracle
+                .get_price_no_holder_than(REQUEST_HASH, u64::MAX)
+                .unwrap();
+            assert_eq!(price.prices, vec![1_000]);
+            assert_eq!(price.sequence, None);
+
+            // migrating again leaves the entries and the configuration as they are, and the signers
+            // still update the feed
+            acelon_oracle.migrate(vec![REQUEST_HASH], vec![]).unwrap();
+            assert_eq!(acelon_oracle.get_owners_count(), 2);
+            let result =
+                acelon_oracle.update_price_feeds(vec![PAYLOAD.to_vec()], vec![vec![SIGNATURE]]);
+            assert_eq!(result, Ok(()));
+            let price = acelon_oracle
+                .get_price_no_holder_than(REQUEST_HASH, u64::MAX)
+                .unwrap();
+            assert_eq!(price.timestamp, PAYLOAD_TIMESTAMP);
+        }
+
+        /// The default caller of the off-chain test environment.
+        fn owner() -> AccountId {
+            ink::env::test::default_accounts::<ink::env::DefaultEnvironment>().alice
+        }
+
+        fn oracle() -> AccountId {
+            blake2_256(
+                hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020")
+                    .as_slice(),
+            )
+            .into()
+        }
+
+        fn account_of(secret_key: &SecretKey) -> AccountId {
+            let public_key = PublicKey::from_secret_key(&Secp256k1::signing_only(), secret_key);
+            blake2_256(&public_key.serialize()).into()
+        }
+
+        fn sign(secret_key: &SecretKey, payload: &[u8]) -> Signature {
+            let message = Message::from_digest(blake2_256(payload));
+            let (recovery_id, signature) = Secp256k1::signing_only()
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut result = [0u8; 65];
+            result[..64].copy_from_slice(&signature);
+            result[64] = recovery_id.to_i32() as u8;
+            result
+        }
+
+        #[inline(always)]
+        fn blake2<const N: usize>(data: &[u8]) -> [u8; N] {
+            blake2b_simd::Params::new()
+                .hash_length(N)
+                .hash(data)
+                .as_bytes()
+                .try_into()
+                .expect("slice is always the necessary length")
+        }
+
+        pub fn blake2_256(data: &[u8]) -> [u8; 32] {
+            blake2(data)
+        }
+
+        /// Runs against a node with `cargo test --features e2e-tests`.
+        #[cfg(feature = "e2e-tests")]
+        mod e2e_tests {
+            use super::*;
+            use acelon_oracle_v1::{
+                AcelonOracle as AcelonOracleV1, AcelonOracleRef as AcelonOracleV1Ref,
+            };
+            use ink_e2e::ContractsBackend;
+
+            type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+            #[ink_e2e::test]
+            async fn price_feeds_signers_and_config_survive_an_upgrade_from_the_first_version<
+                Client: E2EBackend,
+            >(
+                mut client: Client,
+            ) -> E2EResult<()> {
+                let code_hash = client
+                    .upload("acelon-oracle-contract", &ink_e2e::alice())
+                    .submit()
+                    .await
+                    .expect("upload failed")
+                    .code_hash;
+                let alice = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+                let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+                let mut constructor = AcelonOracleV1Ref::new(
+                    vec![alice],
+                    vec![oracle()],
+                    vec![CERTIFICATE],
+                    1,
+                    1,
+                    u64::MAX,
+                );
+                let contract = client
+                    .instantiate("acelon-oracle-v1", &ink_e2e::alice(), &mut constructor)
+                    .submit()
+                    .await
+                    .expect("instantiate failed");
+                let mut call_builder_v1 = contract.call_builder::<AcelonOracleV1>();
+
+                client
+                    .call(
+                        &ink_e2e::alice(),
+                        &call_builder_v1.set_price(
+                            REQUEST_HASH,
+                            PAYLOAD_TIMESTAMP - 1,
+                            vec![1_000],
+                        ),
+                    )
+                    .submit()
+                    .await
+                    .expect("set_price failed");
+                client
+                    .call(&ink_e2e::alice(), &call_builder_v1.propose_owner(bob))
+                    .submit()
+                    .await
+                    .expect("propose_owner failed");
+                client
+                    .call(&ink_e2e::alice(), &call_builder_v1.set_code(code_hash))
+                    .submit()
+                    .await
+                    .expect("set_code failed");
+
+                let mut call_builder =
+                    ink_e2e::create_call_builder::<AcelonOracle>(contract.account_id);
+                let storage_version = client
+                    .call(&ink_e2e::alice(), &call_builder.get_storage_version())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(storage_version, 1);
+                client
+                    .call(
+                        &ink_e2e::alice(),
+                        &call_builder.migrate(vec![REQUEST_HASH], vec![alice]),
+                    )
+                    .submit()
+                    .await
+                    .expect("migrate failed");
+                let storage_version = client
+                    .call(&ink_e2e::alice(), &call_builder.get_storage_version())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(storage_version, STORAGE_VERSION);
+
+                // the price entry is converted
+                let price = client
+                    .call(&ink_e2e::alice(), &call_builder.get_price(REQUEST_HASH))
+                    .dry_run()
+                    .await?
+                    .return_value()
+                    .expect("the price is migrated");
+                assert_eq!(price.timestamp, PAYLOAD_TIMESTAMP - 1);
+                assert_eq!(price.prices, vec![1_000]);
+                assert_eq!(price.sequence, None);
+
+                // the configuration of the first version is kept, and the new one has its defaults
+                let valid_time_period = client
+                    .call(&ink_e2e::alice(), &call_builder.get_valid_time_period())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(valid_time_period, u64::MAX);
+                let owners_count = client
+                    .call(&ink_e2e::alice(), &call_builder.get_owners_count())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(owners_count, 1);
+                let quorum = client
+                    .call(&ink_e2e::alice(), &call_builder.get_quorum())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(quorum, (1, 1));
+                let max_future_drift = client
+                    .call(&ink_e2e::alice(), &call_builder.get_max_future_drift())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(max_future_drift, None);
+                let is_paused = client
+                    .call(&ink_e2e::alice(), &call_builder.is_paused())
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert!(!is_paused);
+
+                // the owner proposal of the first version has no expiry, it is expired
+                let is_proposed_owner = client
+                    .call(&ink_e2e::alice(), &call_builder.is_proposed_owner(bob))
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert!(!is_proposed_owner);
+
+                // the trusted signers and certificates are kept, and the thresholds still apply
+                let results = client
+                    .call(
+                        &ink_e2e::alice(),
+                        &call_builder.try_update_price_feeds(
+                            vec![PAYLOAD.to_vec(), PAYLOAD.to_vec()],
+                            vec![vec![SIGNATURE], vec![]],
+                        ),
+                    )
+                    .submit()
+                    .await
+                    .expect("try_update_price_feeds failed")
+                    .return_value();
+                assert_eq!(
+                    results,
+                    vec![
+                        PriceUpdateResult::Updated,
+                        PriceUpdateResult::Rejected(Error::NotEnoughValidSignatures),
+                    ]
+                );
+                let price = client
+                    .call(&ink_e2e::alice(), &call_builder.get_price(REQUEST_HASH))
+                    .dry_run()
+                    .await?
+                    .return_value();
+                assert_eq!(price.map(|price| price.timestamp), Ok(PAYLOAD_TIMESTAMP));
+
+                // the owners of the first version still administrate the contract
+                client
+                    .call(&ink_e2e::alice(), &call_builder.update_signers_threshold(2))
+                    .submit()
+                    .await
+                    .expect("update_signers_threshold failed");
+                Ok(())
+            }
         }
     }
 }
@@ -1,6 +1,9 @@
 use ink::{prelude::vec::Vec, primitives::AccountId};
 
-use crate::types::{Certificate, Error, PriceEntry, RequestHash, Signature};
+use crate::types::{
+    Certificate, Error, FeedConfig, PriceEntry, PriceUpdateResult, RequestHash, Signature,
+    SigningDomain,
+};
 
 #[ink::trait_definition]
 pub trait PriceOracle {
@@ -11,17 +14,78 @@ pub trait PriceOracle {
         signatures: Vec<Vec<Signature>>,
     ) -> Result<(), Error>;
 
+    /// Applies every valid update of the batch and returns the result of each update.
+    #[ink(message)]
+    fn try_update_price_feeds(
+        &mut self,
+        update_data: Vec<Vec<u8>>,
+        signatures: Vec<Vec<Signature>>,
+    ) -> Vec<PriceUpdateResult>;
+
     #[ink(message)]
     fn price_feed_exists(&self, request_hash: RequestHash) -> bool;
 
     #[ink(message)]
     fn get_valid_time_period(&self) -> u64;
 
+    /// How far in the future of the block time a payload timestamp can be, unchecked if `None`.
+    #[ink(message)]
+    fn get_max_future_drift(&self) -> Option<u64>;
+
+    /// How far in the past of the block time a payload timestamp can be, unchecked if `None`.
+    #[ink(message)]
+    fn get_max_submission_age(&self) -> Option<u64>;
+
+    /// The configuration overrides of a feed, if any.
+    #[ink(message)]
+    fn get_feed_config(&self, request_hash: RequestHash) -> Option<FeedConfig>;
+
+    /// Returns true if only updates of registered feeds are accepted.
+    #[ink(message)]
+    fn get_registered_feeds_only(&self) -> bool;
+
+    /// Returns true if legacy payloads, not bound to the contract by a signing domain, are rejected.
+    #[ink(message)]
+    fn get_require_signing_domain(&self) -> bool;
+
+    /// The signing domain binding updates to the contract.
+    #[ink(message)]
+    fn get_signing_domain(&self) -> SigningDomain;
+
+    #[ink(message)]
+    fn is_feed_registered(&self, request_hash: RequestHash) -> bool;
+
+    /// Returns the price of a feed if it is not older than its valid time period.
+    ///
+    /// Fails with `Error::Paused` while the oracle or the feed is paused.
+    #[ink(message)]
+    fn get_price(&self, request_hash: RequestHash) -> Result<PriceEntry, Error>;
+
+    /// Returns the price of a feed if it is not older than `age` milliseconds.
+    ///
+    /// Fails with `Error::Paused` while the oracle or the feed is paused.
     #[ink(message)]
-    fn get_price(&self, request_hash: RequestHash) -> Option<PriceEntry>;
+    fn get_price_no_holder_than(
+        &self,
+        request_hash: RequestHash,
+        age: u64,
+    ) -> Result<PriceEntry, Error>;
 
+    /// Returns the result of `get_price` for each feed, in the order of `request_hashes`.
+    ///
+    /// A missing price fails with `Error::PriceNotFound` if it was never set, or with
+    /// `Error::PriceStale` if it is too old or expired.
     #[ink(message)]
-    fn get_price_no_holder_than(&self, request_hash: RequestHash, age: u64) -> Option<PriceEntry>;
+    fn get_prices(&self, request_hashes: Vec<RequestHash>) -> Vec<Result<PriceEntry, Error>>;
+
+    /// Returns the result of `get_price_no_holder_than` for each feed, in the order of
+    /// `request_hashes`.
+    #[ink(message)]
+    fn get_prices_no_older_than(
+        &self,
+        request_hashes: Vec<RequestHash>,
+        age: u64,
+    ) -> Vec<Result<PriceEntry, Error>>;
 
     #[ink(message)]
     fn update_signers_threshold(&mut self, new_threshold: u8) -> Result<(), Error>;
@@ -32,6 +96,36 @@ pub trait PriceOracle {
     #[ink(message)]
     fn update_valid_time_period(&mut self, new_time_period: u64) -> Result<(), Error>;
 
+    #[ink(message)]
+    fn update_max_future_drift(&mut self, max_future_drift: Option<u64>) -> Result<(), Error>;
+
+    #[ink(message)]
+    fn update_max_submission_age(&mut self, max_submission_age: Option<u64>) -> Result<(), Error>;
+
+    #[ink(message)]
+    fn update_registered_feeds_only(&mut self, registered_feeds_only: bool) -> Result<(), Error>;
+
+    #[ink(message)]
+    fn update_require_signing_domain(&mut self, require_signing_domain: bool) -> Result<(), Error>;
+
+    #[ink(message)]
+    fn register_feed(&mut self, request_hash: RequestHash) -> Result<(), Error>;
+
+    #[ink(message)]
+    fn deregister_feed(&mut self, request_hash: RequestHash) -> Result<(), Error>;
+
+    /// Overrides the global thresholds and valid time period for a feed.
+    #[ink(message)]
+    fn set_feed_config(
+        &mut self,
+        request_hash: RequestHash,
+        config: FeedConfig,
+    ) -> Result<(), Error>;
+
+    /// Removes the overrides of a feed, which falls back to the global configuration.
+    #[ink(message)]
+    fn clear_feed_config(&mut self, request_hash: RequestHash) -> Result<(), Error>;
+
     #[ink(message)]
     fn add_trusted_signer(&mut self, new_trusted_signer: AccountId) -> Result<(), Error>;
 
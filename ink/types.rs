@@ -1,4 +1,7 @@
-use ink::prelude::vec::Vec;
+use ink::{
+    prelude::vec::Vec,
+    primitives::{AccountId, Hash},
+};
 use scale::{Decode, Encode};
 use scale_info::TypeInfo;
 
@@ -6,11 +9,93 @@ pub type Signature = [u8; 65];
 pub type Certificate = [u8; 32];
 pub type RequestHash = [u8; 32];
 
-#[derive(Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
 #[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
 pub struct PriceEntry {
     pub timestamp: u64,
     pub prices: Vec<u128>,
+    /// The decimals of each price, empty for prices of a V1 payload.
+    pub decimals: Vec<u8>,
+    /// The confidence of each price, empty for prices of a V1 payload.
+    pub confidences: Vec<u128>,
+    /// The price is stale after this timestamp, `None` for prices of a V1 payload.
+    pub valid_until: Option<u64>,
+    /// The sequence number of the payload, `None` for prices of a V1 payload.
+    pub sequence: Option<u64>,
+}
+
+impl PriceEntry {
+    /// Returns true if the price has an expiry before `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.valid_until
+            .map(|valid_until| valid_until < now)
+            .unwrap_or(false)
+    }
+}
+
+/// A price entry as stored by the contract before the V2 payloads, storage version 1.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct PriceEntryV1 {
+    pub timestamp: u64,
+    pub prices: Vec<u128>,
+}
+
+impl From<PriceEntryV1> for PriceEntry {
+    fn from(value: PriceEntryV1) -> Self {
+        Self {
+            timestamp: value.timestamp,
+            prices: value.prices,
+            decimals: Vec::new(),
+            confidences: Vec::new(),
+            valid_until: None,
+            sequence: None,
+        }
+    }
+}
+
+/// The bytes starting the signed data of an update bound to a [`SigningDomain`].
+pub const SIGNING_DOMAIN_TAG: [u8; 6] = *b"acelon";
+
+/// Binds the signed data of an update to a single contract, so it cannot be replayed on other
+/// deployments trusting the same signers.
+///
+/// Bound data is `SIGNING_DOMAIN_TAG ++ SigningDomain ++ PricePayload`, SCALE encoded, and the
+/// signatures cover all of it. Data without the tag is a legacy payload.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct SigningDomain {
+    pub version: u8,
+    /// The address of the contract.
+    pub target: [u8; 32],
+}
+
+impl SigningDomain {
+    /// The version of the signing domain accepted by the contract.
+    pub const VERSION: u8 = 1;
+
+    pub fn new(target: [u8; 32]) -> Self {
+        Self {
+            version: Self::VERSION,
+            target,
+        }
+    }
+
+    /// Splits signed data into its signing domain, `None` for a legacy payload, and its payload.
+    pub fn split(data: &[u8]) -> (Option<Self>, &[u8]) {
+        if let Some(mut rest) = data.strip_prefix(SIGNING_DOMAIN_TAG.as_slice()) {
+            if let Ok(signing_domain) = Self::decode(&mut rest) {
+                return (Some(signing_domain), rest);
+            }
+        }
+        (None, data)
+    }
+
+    /// Returns the data to sign to bind `payload` to this domain.
+    pub fn bind(&self, payload: &[u8]) -> Vec<u8> {
+        let mut data = SIGNING_DOMAIN_TAG.to_vec();
+        self.encode_to(&mut data);
+        data.extend_from_slice(payload);
+        data
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
@@ -22,11 +107,308 @@ pub struct PricePayload {
     pub request_hash: RequestHash,
 }
 
-#[derive(scale_info::TypeInfo, Debug, PartialEq, Eq, Encode, Decode)]
+/// The bytes starting an encoded [`PricePayloadV2`].
+///
+/// `0xff` can't start a V1 payload, as it would announce a number of prices that does not fit a `u32`.
+pub const PRICE_PAYLOAD_V2_MAGIC: [u8; 4] = [0xff, b'a', b'p', 2];
+
+/// The second version of the price payload, encoded after [`PRICE_PAYLOAD_V2_MAGIC`].
+#[derive(Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+pub struct PricePayloadV2 {
+    pub prices: Vec<u128>,
+    /// The decimals of each price.
+    pub decimals: Vec<u8>,
+    /// The confidence of each price, in the unit of the price.
+    pub confidences: Vec<u128>,
+    pub timestamp: u64,
+    /// The payload is rejected, and its price stale, after this timestamp.
+    pub valid_until: u64,
+    /// Orders the payloads of a feed with the same timestamp.
+    pub sequence: u64,
+    pub certificates: Vec<Certificate>,
+    pub request_hash: RequestHash,
+}
+
+/// A price payload of any supported version.
+#[derive(Clone, Eq, PartialEq)]
+pub enum VersionedPricePayload {
+    V1(PricePayload),
+    V2(PricePayloadV2),
+}
+
+impl VersionedPricePayload {
+    /// Decodes a V2 payload if `data` starts with [`PRICE_PAYLOAD_V2_MAGIC`], a V1 payload otherwise.
+    pub fn decode_from(data: &[u8]) -> Result<Self, scale::Error> {
+        match data.strip_prefix(PRICE_PAYLOAD_V2_MAGIC.as_slice()) {
+            Some(mut payload) => PricePayloadV2::decode(&mut payload).map(Self::V2),
+            None => PricePayload::decode(&mut &data[..]).map(Self::V1),
+        }
+    }
+
+    /// Encodes the payload, as read by [`Self::decode_from`].
+    #[cfg(test)]
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::V1(payload) => payload.encode(),
+            Self::V2(payload) => {
+                let mut data = PRICE_PAYLOAD_V2_MAGIC.to_vec();
+                payload.encode_to(&mut data);
+                data
+            }
+        }
+    }
+
+    /// Returns true if the decimals and the confidences, if any, match the prices.
+    pub fn is_consistent(&self) -> bool {
+        match self {
+            Self::V1(_) => true,
+            Self::V2(payload) => {
+                payload.decimals.len() == payload.prices.len()
+                    && payload.confidences.len() == payload.prices.len()
+            }
+        }
+    }
+
+    pub fn request_hash(&self) -> RequestHash {
+        match self {
+            Self::V1(payload) => payload.request_hash,
+            Self::V2(payload) => payload.request_hash,
+        }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Self::V1(payload) => payload.timestamp,
+            Self::V2(payload) => payload.timestamp,
+        }
+    }
+
+    pub fn certificates(&self) -> &[Certificate] {
+        match self {
+            Self::V1(payload) => &payload.certificates,
+            Self::V2(payload) => &payload.certificates,
+        }
+    }
+
+    pub fn valid_until(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.valid_until),
+        }
+    }
+
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.sequence),
+        }
+    }
+
+    /// The price entry stored for the payload.
+    pub fn into_price_entry(self) -> PriceEntry {
+        match self {
+            Self::V1(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                decimals: Vec::new(),
+                confidences: Vec::new(),
+                valid_until: None,
+                sequence: None,
+            },
+            Self::V2(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                decimals: payload.decimals,
+                confidences: payload.confidences,
+                valid_until: Some(payload.valid_until),
+                sequence: Some(payload.sequence),
+            },
+        }
+    }
+}
+
+/// Overrides of the global oracle configuration for a single feed, `None` falls back to the global value.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct FeedConfig {
+    pub valid_signers_threshold: Option<u8>,
+    pub valid_sources_threshold: Option<u8>,
+    pub valid_time_period: Option<u64>,
+}
+
+/// Proposals can be approved for a week by default.
+pub const DEFAULT_PROPOSAL_TTL: u64 = 7 * 24 * 3_600_000;
+
+/// The oracle configuration added after the first storage version.
+///
+/// It is stored apart from the root of the contract, whose layout can't change without breaking the
+/// deployments upgraded from the first version, and written by `migrate` on those deployments.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct Config {
+    /// How far in the future of the block time a payload timestamp can be, unchecked if `None`.
+    pub max_future_drift: Option<u64>,
+    /// How far in the past of the block time a payload timestamp can be, unchecked if `None`.
+    pub max_submission_age: Option<u64>,
+    /// When set, only updates of the registered feeds are accepted.
+    pub registered_feeds_only: bool,
+    /// When set, legacy payloads are rejected and every update has to be bound to the contract by a
+    /// [`SigningDomain`].
+    pub require_signing_domain: bool,
+    /// The number of owners, which can't drop to zero.
+    pub owners_count: u32,
+    /// The number of owner approvals an admin action needs.
+    pub quorum: u8,
+    /// The number of owner approvals a defensive action, see [`AdminAction::is_defensive`], needs.
+    pub defensive_quorum: u8,
+    /// How long a proposal can be approved, in milliseconds.
+    pub proposal_ttl: u64,
+    pub next_proposal_id: u32,
+    /// When set, every update is rejected and every price read fails.
+    pub paused: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_future_drift: None,
+            max_submission_age: None,
+            registered_feeds_only: false,
+            require_signing_domain: false,
+            owners_count: 0,
+            quorum: 1,
+            defensive_quorum: 1,
+            proposal_ttl: DEFAULT_PROPOSAL_TTL,
+            next_proposal_id: 0,
+            paused: false,
+        }
+    }
+}
+
+/// What a pause applies to.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub enum PauseScope {
+    /// Every update is rejected and every price read fails.
+    All,
+    /// The updates and the price reads of these feeds only.
+    Feeds(Vec<RequestHash>),
+}
+
+/// An admin action, executed once enough owners approved it.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub enum AdminAction {
+    UpdateSignersThreshold(u8),
+    UpdateSourcesThreshold(u8),
+    UpdateValidTimePeriod(u64),
+    UpdateMaxFutureDrift(Option<u64>),
+    UpdateMaxSubmissionAge(Option<u64>),
+    UpdateRegisteredFeedsOnly(bool),
+    UpdateRequireSigningDomain(bool),
+    RegisterFeed(RequestHash),
+    DeregisterFeed(RequestHash),
+    SetFeedConfig(RequestHash, FeedConfig),
+    ClearFeedConfig(RequestHash),
+    AddTrustedSigner(AccountId),
+    RemoveTrustedSigner(AccountId),
+    AddCertificate(Certificate),
+    RemoveCertificate(Certificate),
+    ProposeOwner(AccountId),
+    CancelOwnerProposal(AccountId),
+    RemoveOwner(AccountId),
+    AddGuardian(AccountId),
+    RemoveGuardian(AccountId),
+    Unpause(PauseScope),
+    UpdateQuorum {
+        quorum: u8,
+        defensive_quorum: u8,
+    },
+    UpdateProposalTtl(u64),
+    /// Replaces the code of the contract with the code uploaded under this hash.
+    Upgrade(Hash),
+}
+
+impl AdminAction {
+    /// Returns true for the actions that only withdraw trust, which need the lower defensive quorum.
+    pub fn is_defensive(&self) -> bool {
+        matches!(
+            self,
+            Self::RemoveTrustedSigner(_)
+                | Self::RemoveCertificate(_)
+                | Self::CancelOwnerProposal(_)
+        )
+    }
+}
+
+/// A pending admin action with the owners who approved it.
+#[derive(Debug, Clone, Eq, PartialEq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(ink::storage::traits::StorageLayout))]
+pub struct Proposal {
+    pub action: AdminAction,
+    pub proposer: AccountId,
+    pub approvals: Vec<AccountId>,
+    /// The proposal can't be approved after this timestamp.
+    pub expires_at: u64,
+}
+
+#[derive(scale_info::TypeInfo, Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
 pub enum Error {
     NotEnoughValidSignatures,
     InvalidPayload,
     NotEnoughValidSources,
     NotOwner,
     NotProposedOwner,
+    /// The payload timestamp is further in the future than the max future drift.
+    TimestampInFuture,
+    /// The payload timestamp is older than the max submission age.
+    TimestampTooOld,
+    /// The registered feeds only mode is set and the feed is not registered, or the deregistered feed is
+    /// not registered.
+    FeedNotRegistered,
+    /// The data is bound to another deployment, or is a legacy payload while a signing domain is required.
+    InvalidSigningDomain,
+    /// The payload is past its `valid_until` timestamp.
+    PayloadExpired,
+    /// The caller is neither an owner nor a guardian.
+    NotGuardian,
+    /// The oracle or the feed is paused.
+    Paused,
+    /// No price was ever set for the feed.
+    PriceNotFound,
+    /// The price of the feed is older than its valid time period, or expired.
+    PriceStale,
+    /// The action needs the approval of several owners, see `propose`.
+    QuorumRequired,
+    /// The quorums must be at least 1, the defensive quorum at most the quorum, and the quorum at
+    /// most the number of owners.
+    InvalidQuorum,
+    ProposalNotFound,
+    /// The proposal can no longer be approved.
+    ProposalExpired,
+    /// The caller already approved the proposal.
+    AlreadyApproved,
+    /// Only the proposer can cancel a proposal before it expires.
+    NotProposer,
+    /// The last owner can't be removed.
+    LastOwner,
+    /// The owner proposal can no longer be accepted.
+    OwnerProposalExpired,
+    /// The code hash is unknown to the chain.
+    UpgradeFailed,
+    /// A price entry is in neither the current nor the previous storage layout.
+    MigrationFailed,
+    /// The feed is already registered.
+    FeedAlreadyRegistered,
+}
+
+/// The outcome of a single price update of a `try_update_price_feeds` batch.
+#[derive(scale_info::TypeInfo, Debug, Clone, Copy, PartialEq, Eq, Encode, Decode)]
+pub enum PriceUpdateResult {
+    /// The price feed was updated.
+    Updated,
+    /// The update was valid, but the stored price is at least as recent.
+    NotMoreRecent,
+    /// The update was rejected.
+    Rejected(Error),
 }
@@ -0,0 +1,274 @@
+//! JSON-RPC interface for the Acelon oracle pallet, built on top of [`AcelonOracleRuntimeApi`].
+
+use std::{fmt::Debug, marker::PhantomData, sync::Arc};
+
+use futures::{stream, FutureExt, StreamExt};
+use jsonrpsee::{
+    core::RpcResult,
+    proc_macros::rpc,
+    types::error::{ErrorObject, ErrorObjectOwned},
+    PendingSubscriptionSink,
+};
+use pallet_acelon_oracle::{
+    runtime_api::AcelonOracleApi as AcelonOracleRuntimeApi,
+    types::{PriceEntry, RequestHash},
+};
+use sc_client_api::BlockchainEvents;
+use sc_rpc::{utils::pipe_from_stream, SubscriptionTaskExecutor};
+use scale::Codec;
+use serde::{Deserialize, Serialize};
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_core::{Get, H256};
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::traits::Block as BlockT;
+
+#[cfg(test)]
+mod tests;
+
+/// The maximum number of feeds returned by a single `acelon_listFeeds` call.
+pub const MAX_LIST_FEEDS_LIMIT: u32 = 100;
+
+/// Error code returned when a runtime API call fails.
+const RUNTIME_ERROR: i32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriceEntry {
+    pub timestamp: u64,
+    /// Prices are `u128` values, which are returned as hex strings when they do not fit a JSON number.
+    pub prices: Vec<NumberOrHex>,
+    /// The decimals of each price, empty for prices of a V1 payload.
+    pub decimals: Vec<u8>,
+    pub confidences: Vec<NumberOrHex>,
+    pub valid_until: Option<u64>,
+    pub sequence: Option<u64>,
+}
+
+impl<MaxPrices: Get<u32>> From<PriceEntry<MaxPrices>> for RpcPriceEntry {
+    fn from(value: PriceEntry<MaxPrices>) -> Self {
+        Self {
+            timestamp: value.timestamp,
+            prices: value.prices.into_iter().map(number_or_hex).collect(),
+            decimals: value.decimals.into_inner(),
+            confidences: value.confidences.into_iter().map(number_or_hex).collect(),
+            valid_until: value.valid_until,
+            sequence: value.sequence,
+        }
+    }
+}
+
+/// Returns `value` as a JSON number when it fits a `u64`, as a hex string otherwise.
+fn number_or_hex(value: u128) -> NumberOrHex {
+    u64::try_from(value)
+        .map(NumberOrHex::Number)
+        .unwrap_or_else(|_| NumberOrHex::Hex(value.into()))
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriceFeed {
+    pub request_hash: H256,
+    pub price_entry: RpcPriceEntry,
+}
+
+impl<MaxPrices: Get<u32>> From<(RequestHash, PriceEntry<MaxPrices>)> for RpcPriceFeed {
+    fn from((request_hash, price_entry): (RequestHash, PriceEntry<MaxPrices>)) -> Self {
+        Self {
+            request_hash: request_hash.into(),
+            price_entry: price_entry.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcPriceUpdate<BlockHash> {
+    pub block_hash: BlockHash,
+    pub request_hash: H256,
+    pub price_entry: RpcPriceEntry,
+}
+
+#[rpc(client, server)]
+pub trait AcelonOracleApi<BlockHash> {
+    /// Returns the price of a feed if it is not older than the valid time period.
+    #[method(name = "acelon_getPrice")]
+    fn get_price(
+        &self,
+        request_hash: H256,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<RpcPriceEntry>>;
+
+    /// Returns the price of a feed if it is not older than `age` milliseconds.
+    #[method(name = "acelon_getPriceNoOlderThan")]
+    fn get_price_no_older_than(
+        &self,
+        request_hash: H256,
+        age: u64,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Option<RpcPriceEntry>>;
+
+    /// Returns up to `limit` feeds (capped at [`MAX_LIST_FEEDS_LIMIT`]) after the `start_after` key.
+    ///
+    /// Pass the `requestHash` of the last returned feed as `start_after` to get the next page.
+    #[method(name = "acelon_listFeeds")]
+    fn list_feeds(
+        &self,
+        start_after: Option<H256>,
+        limit: u32,
+        at: Option<BlockHash>,
+    ) -> RpcResult<Vec<RpcPriceFeed>>;
+
+    /// Notifies every price feed update included in a new best block.
+    #[subscription(
+        name = "acelon_subscribePriceUpdates" => "acelon_priceUpdate",
+        unsubscribe = "acelon_unsubscribePriceUpdates",
+        item = RpcPriceUpdate<BlockHash>
+    )]
+    fn subscribe_price_updates(&self);
+}
+
+pub struct AcelonOracle<C, Block, MaxPrices> {
+    client: Arc<C>,
+    executor: SubscriptionTaskExecutor,
+    _marker: PhantomData<(Block, MaxPrices)>,
+}
+
+impl<C, Block, MaxPrices> AcelonOracle<C, Block, MaxPrices> {
+    pub fn new(client: Arc<C>, executor: SubscriptionTaskExecutor) -> Self {
+        Self {
+            client,
+            executor,
+            _marker: Default::default(),
+        }
+    }
+}
+
+fn runtime_error(error: impl Debug) -> ErrorObjectOwned {
+    ErrorObject::owned(
+        RUNTIME_ERROR,
+        "Unable to query the Acelon oracle runtime API.",
+        Some(format!("{:?}", error)),
+    )
+}
+
+/// Returns up to `limit` feeds (capped at [`MAX_LIST_FEEDS_LIMIT`]) stored at block `at`.
+fn list_feeds_at<C, Block, MaxPrices>(
+    client: &C,
+    at: <Block as BlockT>::Hash,
+    start_after: Option<H256>,
+    limit: u32,
+) -> RpcResult<Vec<RpcPriceFeed>>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: AcelonOracleRuntimeApi<Block, MaxPrices>,
+    MaxPrices: Get<u32> + Codec,
+{
+    let feeds = client
+        .runtime_api()
+        .list_feeds(
+            at,
+            start_after.map(|key| key.0),
+            limit.min(MAX_LIST_FEEDS_LIMIT),
+        )
+        .map_err(runtime_error)?;
+    Ok(feeds.into_iter().map(Into::into).collect())
+}
+
+/// Returns the price updates deposited in a block.
+///
+/// A failing runtime call only skips the updates of this block, so the subscription stays open.
+fn price_updates_at<C, Block, MaxPrices>(
+    client: &C,
+    block_hash: <Block as BlockT>::Hash,
+) -> Vec<RpcPriceUpdate<<Block as BlockT>::Hash>>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>,
+    C::Api: AcelonOracleRuntimeApi<Block, MaxPrices>,
+    MaxPrices: Get<u32> + Codec,
+{
+    client
+        .runtime_api()
+        .price_feed_updates(block_hash)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(request_hash, price_entry)| RpcPriceUpdate {
+            block_hash,
+            request_hash: request_hash.into(),
+            price_entry: price_entry.into(),
+        })
+        .collect()
+}
+
+impl<C, Block, MaxPrices> AcelonOracleApiServer<<Block as BlockT>::Hash>
+    for AcelonOracle<C, Block, MaxPrices>
+where
+    Block: BlockT,
+    C: ProvideRuntimeApi<Block>
+        + HeaderBackend<Block>
+        + BlockchainEvents<Block>
+        + Send
+        + Sync
+        + 'static,
+    C::Api: AcelonOracleRuntimeApi<Block, MaxPrices>,
+    MaxPrices: Get<u32> + Codec + Send + Sync + 'static,
+{
+    fn get_price(
+        &self,
+        request_hash: H256,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<RpcPriceEntry>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let price = self
+            .client
+            .runtime_api()
+            .get_price(at, request_hash.0)
+            .map_err(runtime_error)?;
+        Ok(price.map(Into::into))
+    }
+
+    fn get_price_no_older_than(
+        &self,
+        request_hash: H256,
+        age: u64,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Option<RpcPriceEntry>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        let price = self
+            .client
+            .runtime_api()
+            .get_price_no_older_than(at, request_hash.0, age)
+            .map_err(runtime_error)?;
+        Ok(price.map(Into::into))
+    }
+
+    fn list_feeds(
+        &self,
+        start_after: Option<H256>,
+        limit: u32,
+        at: Option<<Block as BlockT>::Hash>,
+    ) -> RpcResult<Vec<RpcPriceFeed>> {
+        let at = at.unwrap_or_else(|| self.client.info().best_hash);
+        list_feeds_at::<_, Block, MaxPrices>(&*self.client, at, start_after, limit)
+    }
+
+    fn subscribe_price_updates(&self, pending: PendingSubscriptionSink) {
+        let client = self.client.clone();
+        let updates = self
+            .client
+            .import_notification_stream()
+            .filter(|notification| futures::future::ready(notification.is_new_best))
+            .flat_map(move |notification| {
+                stream::iter(price_updates_at::<_, Block, MaxPrices>(
+                    &*client,
+                    notification.hash,
+                ))
+            });
+
+        let fut = pipe_from_stream(pending, updates.boxed());
+        self.executor
+            .spawn("acelon-oracle-price-updates", Some("rpc"), fut.boxed());
+    }
+}
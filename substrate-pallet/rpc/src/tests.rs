@@ -0,0 +1,193 @@
+use pallet_acelon_oracle::{
+    runtime_api::AcelonOracleApi,
+    types::{
+        FeedConfig, FeedInfo, PriceEntry, PriceError, PriceHistoryEntry, RequestHash,
+        SigningDomain, CU32,
+    },
+};
+use sp_api::ApiRef;
+use sp_runtime::{
+    generic,
+    traits::{BlakeTwo256, NumberFor},
+    OpaqueExtrinsic,
+};
+
+use super::*;
+
+type Block = generic::Block<generic::Header<u32, BlakeTwo256>, OpaqueExtrinsic>;
+type MaxPrices = CU32<4>;
+
+fn price_entry(timestamp: u64, prices: Vec<u128>) -> PriceEntry<MaxPrices> {
+    PriceEntry {
+        timestamp,
+        prices: prices.try_into().unwrap(),
+        decimals: Default::default(),
+        confidences: Default::default(),
+        valid_until: None,
+        sequence: None,
+    }
+}
+
+/// A client whose runtime API returns the configured feeds and updates.
+#[derive(Clone, Default)]
+struct TestApi {
+    feeds: Vec<(RequestHash, PriceEntry<MaxPrices>)>,
+    updates: Vec<(RequestHash, PriceEntry<MaxPrices>)>,
+}
+
+struct RuntimeApi {
+    inner: TestApi,
+}
+
+impl ProvideRuntimeApi<Block> for TestApi {
+    type Api = RuntimeApi;
+
+    fn runtime_api(&self) -> ApiRef<'_, Self::Api> {
+        RuntimeApi {
+            inner: self.clone(),
+        }
+        .into()
+    }
+}
+
+sp_api::mock_impl_runtime_apis! {
+    impl AcelonOracleApi<Block, MaxPrices> for RuntimeApi {
+        fn get_price(request_hash: RequestHash) -> Option<PriceEntry<MaxPrices>> {
+            self.inner
+                .feeds
+                .iter()
+                .find(|(key, _)| *key == request_hash)
+                .map(|(_, price_entry)| price_entry.clone())
+        }
+
+        fn get_price_no_older_than(_request_hash: RequestHash, _age: u64) -> Option<PriceEntry<MaxPrices>> {
+            None
+        }
+
+        fn price_feed_exists(request_hash: RequestHash) -> bool {
+            self.inner.feeds.iter().any(|(key, _)| *key == request_hash)
+        }
+
+        fn valid_signers_threshold() -> u8 {
+            Default::default()
+        }
+
+        fn valid_sources_threshold() -> u8 {
+            Default::default()
+        }
+
+        fn valid_time_period() -> u64 {
+            Default::default()
+        }
+
+        fn signing_domain() -> SigningDomain {
+            SigningDomain {
+                version: SigningDomain::VERSION,
+                target: Default::default(),
+            }
+        }
+
+        fn feed_config(_request_hash: RequestHash) -> Option<FeedConfig> {
+            None
+        }
+
+        fn list_feeds(start_after: Option<RequestHash>, limit: u32) -> Vec<(RequestHash, PriceEntry<MaxPrices>)> {
+            self.inner
+                .feeds
+                .iter()
+                .filter(|(request_hash, _)| start_after.map_or(true, |key| *request_hash > key))
+                .take(limit as usize)
+                .cloned()
+                .collect()
+        }
+
+        fn feed_metadata(_request_hash: RequestHash) -> Option<FeedInfo> {
+            None
+        }
+
+        fn registered_feeds(_start_after: Option<RequestHash>, _limit: u32) -> Vec<(RequestHash, FeedInfo)> {
+            Vec::new()
+        }
+
+        fn price_history(_request_hash: RequestHash, _n: u32) -> Vec<PriceHistoryEntry<MaxPrices, NumberFor<Block>>> {
+            Vec::new()
+        }
+
+        fn twap(_request_hash: RequestHash, _index: u32, _window: u64) -> Result<u128, PriceError> {
+            Err(PriceError::NotFound)
+        }
+
+        fn price_feed_updates() -> Vec<(RequestHash, PriceEntry<MaxPrices>)> {
+            self.inner.updates.clone()
+        }
+    }
+}
+
+#[test]
+fn test_list_feeds() {
+    let client = TestApi {
+        feeds: (0..150u8)
+            .map(|i| ([i; 32], price_entry(i as u64, vec![i as u128])))
+            .collect(),
+        ..Default::default()
+    };
+    let at = H256::repeat_byte(1);
+
+    let page = list_feeds_at::<_, Block, MaxPrices>(&client, at, None, 2).unwrap();
+    assert_eq!(page.len(), 2);
+    assert_eq!(page[0].request_hash, H256::repeat_byte(0));
+    assert_eq!(page[1].price_entry.timestamp, 1);
+    assert_eq!(page[1].price_entry.prices, vec![NumberOrHex::Number(1)]);
+
+    // the last key of a page is the cursor of the next one
+    let page =
+        list_feeds_at::<_, Block, MaxPrices>(&client, at, Some(page[1].request_hash), 2).unwrap();
+    assert_eq!(page[0].request_hash, H256::repeat_byte(2));
+
+    let page = list_feeds_at::<_, Block, MaxPrices>(&client, at, None, u32::MAX).unwrap();
+    assert_eq!(page.len(), MAX_LIST_FEEDS_LIMIT as usize);
+}
+
+#[test]
+fn test_price_updates() {
+    let request_hash = [1u8; 32];
+    let client = TestApi {
+        updates: vec![
+            (request_hash, price_entry(1, vec![1_000])),
+            (request_hash, price_entry(2, vec![u128::MAX])),
+        ],
+        ..Default::default()
+    };
+    let block_hash = H256::repeat_byte(7);
+
+    let updates = price_updates_at::<_, Block, MaxPrices>(&client, block_hash);
+    assert_eq!(
+        updates,
+        vec![
+            RpcPriceUpdate {
+                block_hash,
+                request_hash: request_hash.into(),
+                price_entry: RpcPriceEntry {
+                    timestamp: 1,
+                    prices: vec![NumberOrHex::Number(1_000)],
+                    decimals: vec![],
+                    confidences: vec![],
+                    valid_until: None,
+                    sequence: None,
+                },
+            },
+            RpcPriceUpdate {
+                block_hash,
+                request_hash: request_hash.into(),
+                price_entry: RpcPriceEntry {
+                    timestamp: 2,
+                    prices: vec![NumberOrHex::Hex(u128::MAX.into())],
+                    decimals: vec![],
+                    confidences: vec![],
+                    valid_until: None,
+                    sequence: None,
+                },
+            },
+        ]
+    );
+}
@@ -1,61 +1,381 @@
-use frame_benchmarking::{benchmarks, whitelist_account};
-use frame_support::traits::Get;
+use frame_benchmarking::{account, benchmarks, whitelist_account, BenchmarkError};
+use frame_support::{
+    sp_runtime::traits::Zero,
+    traits::{EnsureOrigin, Get},
+    BoundedVec,
+};
 use frame_system::RawOrigin;
 use hex_literal::hex;
+use scale::Encode;
+use sp_core::{crypto::KeyTypeId, ecdsa};
 use sp_std::prelude::*;
 
 use super::{
-    types::{CertificateTrustStoreUpdate, ListUpdateOperation, TrustedSignerUpdate},
+    types::{
+        Certificate, CertificateTrustStoreUpdate, DeviationGuard, DeviationMode, FeedConfig,
+        FeedMetadataFor, ListUpdateOperation, PriceEntry, PriceHistoryEntry, PriceObservation,
+        PricePayloadV2For, PriceUpdateResult, RequestHash, TrustedSignerUpdate, UpdateDataFor,
+        UpdateSignaturesFor, VersionedPricePayloadFor,
+    },
     *,
 };
 
+const SIGNER_KEY_TYPE: KeyTypeId = KeyTypeId(*b"acln");
+const PAYLOAD_TIMESTAMP: u64 = 1_000;
+
+fn trusted_signer<T: Config>() -> ecdsa::Public
+where
+    <T as frame_system::Config>::AccountId: From<[u8; 32]>,
+{
+    let signer = sp_io::crypto::ecdsa_generate(SIGNER_KEY_TYPE, None);
+    let account: T::AccountId = sp_io::hashing::blake2_256(signer.as_ref()).into();
+    <TrustedSigner<T>>::insert(account, ());
+    signer
+}
+
+fn sign<T: Config>(signer: &ecdsa::Public, data: &[u8]) -> T::Signature
+where
+    <T as Config>::Signature: From<[u8; 65]>,
+{
+    let signature = sp_io::crypto::ecdsa_sign_prehashed(
+        SIGNER_KEY_TYPE,
+        signer,
+        &sp_io::hashing::blake2_256(data),
+    )
+    .expect("the signer key is in the keystore");
+    <[u8; 65]>::try_from(signature.as_ref())
+        .expect("ecdsa signatures are 65 bytes long")
+        .into()
+}
+
+fn certificate(index: u32) -> Certificate {
+    sp_io::hashing::blake2_256(&(b"certificate", index).encode())
+}
+
+fn request_hash(index: u32) -> RequestHash {
+    sp_io::hashing::blake2_256(&(b"request", index).encode())
+}
+
+fn max_prices<T: Config>() -> BoundedVec<u128, T::MaxPrices> {
+    vec![u128::MAX; T::MaxPrices::get() as usize]
+        .try_into()
+        .expect("the length is MaxPrices")
+}
+
+fn max_feed_metadata<T: Config>() -> FeedMetadataFor<T> {
+    let label = BoundedVec::truncate_from(vec![b'l'; T::MaxSymbolLen::get() as usize]);
+    FeedMetadataFor::<T> {
+        symbol: BoundedVec::truncate_from(vec![b's'; T::MaxSymbolLen::get() as usize]),
+        description: BoundedVec::truncate_from(vec![b'd'; T::MaxDescriptionLen::get() as usize]),
+        decimals: BoundedVec::truncate_from(vec![18; T::MaxPrices::get() as usize]),
+        labels: BoundedVec::truncate_from(vec![label; T::MaxPrices::get() as usize]),
+    }
+}
+
+fn price_payload<T: Config>(index: u32, certificates: Vec<Certificate>) -> Vec<u8> {
+    VersionedPricePayloadFor::<T>::V2(PricePayloadV2For::<T> {
+        prices: max_prices::<T>(),
+        decimals: BoundedVec::truncate_from(vec![18; T::MaxPrices::get() as usize]),
+        confidences: max_prices::<T>(),
+        timestamp: PAYLOAD_TIMESTAMP,
+        valid_until: u64::MAX,
+        sequence: u64::MAX,
+        certificates: certificates
+            .try_into()
+            .expect("certificates are bounded by MaxCertificates"),
+        request_hash: request_hash(index),
+    })
+    .encode()
+}
+
+/// Creates `updates` signed price updates, bound to the chain by its signing domain, for registered
+/// feeds that already have an older price, a full history, full observations and a deviation guard
+/// suspending them.
+///
+/// Every update carries `signatures` signatures and `certificates` certificates, only the last of
+/// each is trusted, and its data is padded with trailing bytes to exactly `bytes` bytes.
+fn price_updates<T: Config>(
+    updates: u32,
+    signatures: u32,
+    certificates: u32,
+    bytes: u32,
+) -> (Vec<UpdateDataFor<T>>, Vec<UpdateSignaturesFor<T>>)
+where
+    <T as frame_system::Config>::AccountId: From<[u8; 32]>,
+    <T as Config>::Signature: From<[u8; 65]>,
+{
+    <ValidSignersThreshold<T>>::put(1);
+    <ValidSourcesThreshold<T>>::put(1);
+    <RegisteredFeedsOnly<T>>::put(true);
+    <RequireSigningDomain<T>>::put(true);
+    let signing_domain = Pallet::<T>::signing_domain();
+    let trusted = trusted_signer::<T>();
+    let untrusted = sp_io::crypto::ecdsa_generate(SIGNER_KEY_TYPE, None);
+
+    let mut update_data = Vec::<UpdateDataFor<T>>::new();
+    let mut update_signatures = Vec::<UpdateSignaturesFor<T>>::new();
+    let payload_certificates = (0..certificates).map(certificate).collect::<Vec<_>>();
+    <CertificateTrustStore<T>>::insert(payload_certificates[payload_certificates.len() - 1], ());
+    for i in 0..updates {
+        // every price deviates from the stored one, so the feed gets suspended
+        <PriceFeed<T>>::insert(
+            request_hash(i),
+            PriceEntry {
+                timestamp: PAYLOAD_TIMESTAMP - 1,
+                prices: BoundedVec::truncate_from(vec![1; T::MaxPrices::get() as usize]),
+                decimals: BoundedVec::truncate_from(vec![18; T::MaxPrices::get() as usize]),
+                confidences: BoundedVec::truncate_from(vec![1; T::MaxPrices::get() as usize]),
+                valid_until: Some(u64::MAX),
+                sequence: Some(0),
+            },
+        );
+        <DeviationGuards<T>>::insert(
+            request_hash(i),
+            DeviationGuard {
+                max_deviation_bps: 0,
+                mode: DeviationMode::Suspend,
+            },
+        );
+        <FeedRegistry<T>>::insert(request_hash(i), max_feed_metadata::<T>());
+        <FeedConfigs<T>>::insert(
+            request_hash(i),
+            FeedConfig {
+                valid_signers_threshold: Some(1),
+                valid_sources_threshold: Some(1),
+                valid_time_period: Some(u64::MAX),
+            },
+        );
+        <PriceHistory<T>>::insert(
+            request_hash(i),
+            BoundedVec::<_, T::MaxHistory>::truncate_from(vec![
+                PriceHistoryEntry {
+                    timestamp: PAYLOAD_TIMESTAMP - 1,
+                    prices: max_prices::<T>(),
+                    block_number: Zero::zero(),
+                };
+                T::MaxHistory::get() as usize
+            ]),
+        );
+        <PriceObservations<T>>::insert(
+            request_hash(i),
+            BoundedVec::<_, T::MaxHistory>::truncate_from(vec![
+                PriceObservation {
+                    timestamp: PAYLOAD_TIMESTAMP - 1,
+                    cumulatives: max_prices::<T>(),
+                };
+                T::MaxHistory::get() as usize
+            ]),
+        );
+
+        let mut data = signing_domain.bind(&price_payload::<T>(i, payload_certificates.clone()));
+        // trailing bytes are ignored by the decoding, but they are hashed
+        assert!(
+            data.len() <= bytes as usize,
+            "the payload is longer than {bytes} bytes"
+        );
+        data.resize(bytes as usize, 0);
+        let mut data_signatures: Vec<T::Signature> = (1..signatures)
+            .map(|_| sign::<T>(&untrusted, &data))
+            .collect();
+        data_signatures.push(sign::<T>(&trusted, &data));
+        update_data.push(data.try_into().expect("MaxPayloadLen fits the payload"));
+        update_signatures.push(
+            data_signatures
+                .try_into()
+                .expect("signatures are bounded by MaxSignaturesPerUpdate"),
+        );
+    }
+    (update_data, update_signatures)
+}
+
+/// The batch of [`price_updates`] sent to the update extrinsics, every update padded to `MaxPayloadLen`
+/// bytes.
+fn max_len_price_updates<T: Config>(
+    updates: u32,
+    signatures: u32,
+    certificates: u32,
+) -> (
+    BoundedVec<UpdateDataFor<T>, T::MaxPriceUpdates>,
+    BoundedVec<UpdateSignaturesFor<T>, T::MaxPriceUpdates>,
+)
+where
+    <T as frame_system::Config>::AccountId: From<[u8; 32]>,
+    <T as Config>::Signature: From<[u8; 65]>,
+{
+    let (update_data, update_signatures) =
+        price_updates::<T>(updates, signatures, certificates, T::MaxPayloadLen::get());
+    (
+        update_data
+            .try_into()
+            .expect("updates are bounded by MaxPriceUpdates"),
+        update_signatures
+            .try_into()
+            .expect("updates are bounded by MaxPriceUpdates"),
+    )
+}
+
+/// Asserts the price of the first `updates` feeds was overwritten by [`price_updates`].
+fn assert_written<T: Config>(updates: u32) {
+    for i in 0..updates {
+        assert_eq!(
+            <PriceFeed<T>>::get(request_hash(i)).map(|price_entry| price_entry.timestamp),
+            Some(PAYLOAD_TIMESTAMP)
+        );
+    }
+}
+
 benchmarks! {
     where_clause { where
         <T as frame_system::Config>::AccountId: From<[u8; 32]>,
         <T as Config>::Signature: From<[u8; 65]>,
     }
 
+    // Worst case: every update carries `s` signatures and `c` certificates, only the last of each
+    // being trusted so all of them are checked, is padded to `MaxPayloadLen` bytes and overwrites its
+    // feed, see `set_price`. An update rejected after its verification deposits a `PriceFeedRejected`
+    // event instead of the writes of its feed, so the written updates are the worst case of every
+    // update extrinsic.
     update_price_feeds {
-        let x in 1 .. T::MaxPriceUpdates::get();
-        let caller: T::AccountId = [0u8; 32].into();
+        let u in 1 .. T::MaxPriceUpdates::get();
+        let s in 1 .. T::MaxSignaturesPerUpdate::get();
+        let c in 1 .. T::MaxCertificates::get();
+        let caller: T::AccountId = account("caller", 0, 0);
         whitelist_account!(caller);
-        let mut updates = Vec::<Vec<u8>>::new();
-        let mut signatures = Vec::<Vec<T::Signature>>::new();
-        let oracle: T::AccountId = sp_io::hashing::blake2_256(
-            hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020").as_slice(),
-        ).into();
-        Pallet::<T>::update_trusted_signer(RawOrigin::Root.into(), TrustedSignerUpdate::<T::AccountId> { operation: ListUpdateOperation::Add, item: oracle })?;
-        Pallet::<T>::update_certificate_trust_store(RawOrigin::Root.into(), CertificateTrustStoreUpdate { operation: ListUpdateOperation::Add, item: hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e") })?;
-        Pallet::<T>::update_certificate_trust_store(RawOrigin::Root.into(), CertificateTrustStoreUpdate { operation: ListUpdateOperation::Add, item: hex!("4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384") })?;
-        Pallet::<T>::update_certificate_trust_store(RawOrigin::Root.into(), CertificateTrustStoreUpdate { operation: ListUpdateOperation::Add, item: hex!("b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80") })?;
-        for i in 0..x {
-            updates.push(hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc").to_vec());
-            signatures.push(vec![hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b").into()]);
+        let (update_data, signatures) = max_len_price_updates::<T>(u, s, c);
+    }: _(RawOrigin::Signed(caller), update_data, signatures)
+    verify {
+        assert_written::<T>(u);
+    }
+
+    // Same as `update_price_feeds`, with the results of the batch deposited in a `PriceFeedsProcessed`
+    // event.
+    try_update_price_feeds {
+        let u in 1 .. T::MaxPriceUpdates::get();
+        let s in 1 .. T::MaxSignaturesPerUpdate::get();
+        let c in 1 .. T::MaxCertificates::get();
+        let caller: T::AccountId = account("caller", 0, 0);
+        whitelist_account!(caller);
+        let (update_data, signatures) = max_len_price_updates::<T>(u, s, c);
+    }: _(RawOrigin::Signed(caller), update_data, signatures)
+    verify {
+        assert_written::<T>(u);
+    }
+
+    // Same as `update_price_feeds`, dispatched as an unsigned transaction.
+    submit_price_feeds_unsigned {
+        let u in 1 .. T::MaxPriceUpdates::get();
+        let s in 1 .. T::MaxSignaturesPerUpdate::get();
+        let c in 1 .. T::MaxCertificates::get();
+        let (update_data, signatures) = max_len_price_updates::<T>(u, s, c);
+    }: _(RawOrigin::None, update_data, signatures)
+    verify {
+        assert_written::<T>(u);
+    }
+
+    // Worst case when written: the price overwrites an existing feed with `MaxPrices` prices, a full
+    // history and full observations, and deviates enough to suspend it. Otherwise the update is
+    // skipped as not more recent.
+    set_price {
+        let w in 0 .. 1;
+        let _ = price_updates::<T>(1, 1, 1, T::MaxPayloadLen::get());
+        if w == 0 {
+            <PriceFeed<T>>::mutate(request_hash(0), |entry| {
+                if let Some(entry) = entry {
+                    entry.timestamp = PAYLOAD_TIMESTAMP;
+                    entry.sequence = Some(u64::MAX);
+                }
+            });
         }
-    }: _(RawOrigin::Signed(caller), updates.try_into().unwrap(), signatures.try_into().unwrap())
+        let price_payload = VersionedPricePayloadFor::<T>::decode_from(&price_payload::<T>(0, vec![certificate(0)]))
+            .expect("the payload is valid");
+        let mut result = None;
+    }: {
+        result = Some(Pallet::<T>::set_price(price_payload));
+    }
+    verify {
+        let expected = if w == 0 { PriceUpdateResult::NotMoreRecent } else { PriceUpdateResult::Updated };
+        assert_eq!(result, Some(expected));
+    }
 
     update_signers_threshold {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
         let new_threshold: u8 = 10;
-    }: _(RawOrigin::Root, new_threshold)
+    }: _<T::RuntimeOrigin>(origin, new_threshold)
 
     update_sources_threshold {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
         let new_threshold: u8 = 10;
-    }: _(RawOrigin::Root, new_threshold)
+    }: _<T::RuntimeOrigin>(origin, new_threshold)
 
     update_valid_time_period {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
         let new_time_period: u64 = 3_600_000;
-    }: _(RawOrigin::Root, new_time_period)
+    }: _<T::RuntimeOrigin>(origin, new_time_period)
+
+    update_max_future_drift {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let max_future_drift = Some(5_000u64);
+    }: _<T::RuntimeOrigin>(origin, max_future_drift)
+
+    update_max_submission_age {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let max_submission_age = Some(60_000u64);
+    }: _<T::RuntimeOrigin>(origin, max_submission_age)
+
+    update_registered_feeds_only {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, true)
+
+    update_require_signing_domain {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, true)
 
     update_trusted_signer {
+        let origin = T::SignerManagerOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
         let oracle: T::AccountId = sp_io::hashing::blake2_256(
             hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020").as_slice(),
         ).into();
         let update = TrustedSignerUpdate::<T::AccountId> { operation: ListUpdateOperation::Add, item: oracle };
-    }: _(RawOrigin::Root, update)
+    }: _<T::RuntimeOrigin>(origin, update)
 
     update_certificate_trust_store {
+        let origin = T::CertificateManagerOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
         let update = CertificateTrustStoreUpdate { operation: ListUpdateOperation::Add, item: hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e") };
-    }: _(RawOrigin::Root, update)
+    }: _<T::RuntimeOrigin>(origin, update)
+
+    set_deviation_guard {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let guard = DeviationGuard { max_deviation_bps: 1_000, mode: DeviationMode::Suspend };
+    }: _<T::RuntimeOrigin>(origin, request_hash(0), Some(guard))
+
+    resume_price_feed {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        <SuspendedFeeds<T>>::insert(request_hash(0), ());
+    }: _<T::RuntimeOrigin>(origin, request_hash(0))
+
+    set_feed_config {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        let config = FeedConfig { valid_signers_threshold: Some(2), valid_sources_threshold: Some(2), valid_time_period: Some(60_000) };
+    }: _<T::RuntimeOrigin>(origin, request_hash(0), config)
+
+    clear_feed_config {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        <FeedConfigs<T>>::insert(request_hash(0), FeedConfig::default());
+    }: _<T::RuntimeOrigin>(origin, request_hash(0))
+
+    register_feed {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, request_hash(0), max_feed_metadata::<T>())
+
+    update_feed_metadata {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        <FeedRegistry<T>>::insert(request_hash(0), max_feed_metadata::<T>());
+    }: _<T::RuntimeOrigin>(origin, request_hash(0), max_feed_metadata::<T>())
+
+    deregister_feed {
+        let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+        <FeedRegistry<T>>::insert(request_hash(0), max_feed_metadata::<T>());
+    }: _<T::RuntimeOrigin>(origin, request_hash(0))
 
     impl_benchmark_test_suite!(Pallet, mock::ExtBuilder::default().build(), mock::Test);
 }
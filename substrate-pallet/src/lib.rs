@@ -1,8 +1,10 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 mod migration;
+pub mod runtime_api;
 pub mod traits;
 pub mod types;
+pub mod weights;
 
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
@@ -13,24 +15,53 @@ mod tests;
 
 pub use pallet::*;
 
+/// The largest deviation between the prices at the same index, in basis points of `current`.
+fn deviation_bps(current: &[u128], new: &[u128]) -> u32 {
+    current
+        .iter()
+        .zip(new)
+        .map(|(&current, &new)| {
+            let difference = if current > new {
+                current - new
+            } else {
+                new - current
+            };
+            if difference == 0 {
+                0
+            } else if current == 0 {
+                u32::MAX
+            } else {
+                let bps = difference.saturating_mul(10_000) / current;
+                u32::try_from(bps).unwrap_or(u32::MAX)
+            }
+        })
+        .max()
+        .unwrap_or(0)
+}
+
 #[frame_support::pallet]
 pub mod pallet {
     use frame_support::{
+        dispatch::WithPostDispatchInfo,
         pallet_prelude::{DispatchResultWithPostInfo, *},
-        sp_runtime::traits::IdentifyAccount,
+        sp_runtime::traits::{IdentifyAccount, Zero},
+        traits::UnixTime,
         Blake2_128Concat, Parameter,
     };
-    use frame_system::{
-        ensure_root,
-        pallet_prelude::{OriginFor, *},
-    };
-    use sp_std::prelude::*;
+    use frame_system::pallet_prelude::{OriginFor, *};
+    use sp_std::{collections::btree_set::BTreeSet, prelude::*};
 
     use crate::{
-        traits::{ParameterBound, RecoverableSignature, WeightInfo},
+        traits::{
+            ParameterBound, PriceProvider, RecoverableSignature, UpdatePriceFeedsWeight, WeightInfo,
+        },
         types::{
-            Certificate, CertificateTrustStoreUpdate, ListUpdateOperation, PriceEntry,
-            PricePayload, RequestHash, TrustedSignerUpdate,
+            Certificate, CertificateTrustStoreUpdate, DeviationGuard, DeviationMode, FeedConfig,
+            FeedInfo, FeedMetadataFor, ListUpdateOperation, PriceEntry, PriceError,
+            PriceHistoryEntry, PriceHistoryEntryFor, PriceObservation, PriceObservationFor,
+            PriceUpdateResult, RequestHash, SigningDomain, TrustedSignerUpdate, UpdateCounters,
+            UpdateDataFor, UpdateRejectionReason, UpdateSignaturesFor, VersionedPricePayloadFor,
+            SIGNING_DOMAIN_TAG,
         },
     };
 
@@ -43,15 +74,46 @@ pub mod pallet {
         type MaxCertificates: Get<u32> + ParameterBound;
         #[pallet::constant]
         type MaxPriceUpdates: Get<u32> + Parameter;
+        /// The maximum number of signatures attached to a single price update.
+        #[pallet::constant]
+        type MaxSignaturesPerUpdate: Get<u32> + Parameter;
+        /// The maximum length in bytes of the encoded payload of a single price update.
+        ///
+        /// It must fit a payload with `MaxPrices` prices and `MaxCertificates` certificates.
+        #[pallet::constant]
+        type MaxPayloadLen: Get<u32> + Parameter;
+        /// The number of most recent prices kept in the history of each feed.
+        #[pallet::constant]
+        type MaxHistory: Get<u32> + ParameterBound;
+        /// The maximum length in bytes of the symbol and of each price label of a registered feed.
+        #[pallet::constant]
+        type MaxSymbolLen: Get<u32> + ParameterBound;
+        /// The maximum length in bytes of the description of a registered feed.
+        #[pallet::constant]
+        type MaxDescriptionLen: Get<u32> + ParameterBound;
         /// The signature type. It can be set to [pallet_acelon_oracle::types::Signature].
         type Signature: Parameter + RecoverableSignature<Public = Self::Public>;
         /// The public key type. It can be set to [pallet_acelon_oracle::types::Public].
         type Public: IdentifyAccount<AccountId = Self::AccountId>;
+        /// Time source used to check the age of stored prices. It can be set to `pallet_timestamp::Pallet<Runtime>`.
+        type UnixTime: UnixTime;
+        /// The priority of unsigned price submissions, decreased by the age of the payload in seconds.
+        #[pallet::constant]
+        type UnsignedPriority: Get<TransactionPriority>;
+        /// The number of blocks unsigned price submissions stay valid in the pool.
+        #[pallet::constant]
+        type UnsignedLongevity: Get<TransactionLongevity>;
+        /// Origin allowed to change the thresholds and the valid time period.
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Origin allowed to add and remove trusted signers.
+        type SignerManagerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+        /// Origin allowed to add and remove certificates from the trust store.
+        type CertificateManagerOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 
         type WeightInfo: WeightInfo;
     }
 
-    pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+    pub(crate) const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
     #[pallet::pallet]
     #[pallet::storage_version(STORAGE_VERSION)]
@@ -69,6 +131,27 @@ pub mod pallet {
     #[pallet::getter(fn valid_time_period)]
     pub type ValidTimePeriod<T: Config> = StorageValue<_, u64, ValueQuery>;
 
+    /// How far in the future of the chain time a payload timestamp can be, unchecked if `None`.
+    #[pallet::storage]
+    #[pallet::getter(fn max_future_drift)]
+    pub type MaxFutureDrift<T: Config> = StorageValue<_, u64>;
+
+    /// How far in the past of the chain time a payload timestamp can be, unchecked if `None`.
+    #[pallet::storage]
+    #[pallet::getter(fn max_submission_age)]
+    pub type MaxSubmissionAge<T: Config> = StorageValue<_, u64>;
+
+    /// When set, only updates of the feeds in the registry are accepted.
+    #[pallet::storage]
+    #[pallet::getter(fn registered_feeds_only)]
+    pub type RegisteredFeedsOnly<T: Config> = StorageValue<_, bool, ValueQuery>;
+
+    /// When set, legacy payloads are rejected and every update has to be bound to this chain by a
+    /// [`SigningDomain`].
+    #[pallet::storage]
+    #[pallet::getter(fn require_signing_domain)]
+    pub type RequireSigningDomain<T: Config> = StorageValue<_, bool, ValueQuery>;
+
     #[pallet::storage]
     #[pallet::getter(fn trusted_signer)]
     pub type TrustedSigner<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, ()>;
@@ -82,6 +165,72 @@ pub mod pallet {
     pub type PriceFeed<T: Config> =
         StorageMap<_, Blake2_128Concat, RequestHash, PriceEntry<T::MaxPrices>>;
 
+    /// The last `MaxHistory` prices stored for each feed, newest first.
+    #[pallet::storage]
+    pub type PriceHistory<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        RequestHash,
+        BoundedVec<PriceHistoryEntryFor<T>, T::MaxHistory>,
+        ValueQuery,
+    >;
+
+    /// The deviation guard of each feed, feeds without one accept any price.
+    #[pallet::storage]
+    #[pallet::getter(fn deviation_guard)]
+    pub type DeviationGuards<T: Config> =
+        StorageMap<_, Blake2_128Concat, RequestHash, DeviationGuard>;
+
+    /// The configuration overrides of each feed, the global values apply to the unset ones.
+    #[pallet::storage]
+    #[pallet::getter(fn feed_config)]
+    pub type FeedConfigs<T: Config> = StorageMap<_, Blake2_128Concat, RequestHash, FeedConfig>;
+
+    /// The metadata of the feeds registered by an admin.
+    #[pallet::storage]
+    #[pallet::getter(fn feed_metadata)]
+    pub type FeedRegistry<T: Config> =
+        StorageMap<_, Blake2_128Concat, RequestHash, FeedMetadataFor<T>>;
+
+    /// The feeds suspended by their deviation guard, their price is not provided until an admin resumes them.
+    #[pallet::storage]
+    pub type SuspendedFeeds<T: Config> = StorageMap<_, Blake2_128Concat, RequestHash, ()>;
+
+    /// The last `MaxHistory` observations of the cumulative prices of each feed, newest first.
+    #[pallet::storage]
+    pub type PriceObservations<T: Config> = StorageMap<
+        _,
+        Blake2_128Concat,
+        RequestHash,
+        BoundedVec<PriceObservationFor<T>, T::MaxHistory>,
+        ValueQuery,
+    >;
+
+    #[pallet::genesis_config]
+    #[derive(frame_support::DefaultNoBound)]
+    pub struct GenesisConfig<T: Config> {
+        pub trusted_signers: Vec<T::AccountId>,
+        pub certificates: Vec<Certificate>,
+        pub valid_signers_threshold: u8,
+        pub valid_sources_threshold: u8,
+        pub valid_time_period: u64,
+    }
+
+    #[pallet::genesis_build]
+    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+        fn build(&self) {
+            for signer in &self.trusted_signers {
+                <TrustedSigner<T>>::insert(signer, ());
+            }
+            for certificate in &self.certificates {
+                <CertificateTrustStore<T>>::insert(certificate, ());
+            }
+            <ValidSignersThreshold<T>>::put(self.valid_signers_threshold);
+            <ValidSourcesThreshold<T>>::put(self.valid_sources_threshold);
+            <ValidTimePeriod<T>>::put(self.valid_time_period);
+        }
+    }
+
     #[pallet::event]
     #[pallet::generate_deposit(pub (super) fn deposit_event)]
     pub enum Event<T: Config> {
@@ -93,10 +242,56 @@ pub mod pallet {
         SourcesThresholdUpdated(u8),
         /// Valid time period updated. [new_time_period]
         ValidTimePeriodUpdated(u64),
+        /// Max future drift of payload timestamps updated. [max_future_drift]
+        MaxFutureDriftUpdated(Option<u64>),
+        /// Max submission age of payload timestamps updated. [max_submission_age]
+        MaxSubmissionAgeUpdated(Option<u64>),
+        /// Registered feeds only mode updated. [registered_feeds_only]
+        RegisteredFeedsOnlyUpdated(bool),
+        /// Signing domain requirement updated. [require_signing_domain]
+        RequireSigningDomainUpdated(bool),
         /// Trusted signers updated. [update]
         TrustedSignersUpdated(TrustedSignerUpdate<T::AccountId>),
         /// Certificate trust store updated. [update]
         CertificateTrustStoreUpdated(CertificateTrustStoreUpdate),
+        /// An update of a `try_update_price_feeds` batch was rejected.
+        PriceFeedRejected {
+            index: u32,
+            request_hash: Option<RequestHash>,
+            reason: UpdateRejectionReason,
+        },
+        /// A `try_update_price_feeds` batch was processed, with the result of each update.
+        PriceFeedsProcessed { results: Vec<PriceUpdateResult> },
+        /// The deviation guard of a feed was set or removed.
+        DeviationGuardUpdated {
+            request_hash: RequestHash,
+            guard: Option<DeviationGuard>,
+        },
+        /// An update was skipped because its price deviates too much from the stored one.
+        PriceDeviationRejected {
+            request_hash: RequestHash,
+            deviation_bps: u32,
+        },
+        /// A feed was suspended because the price of an update deviates too much from the stored one.
+        PriceFeedSuspended {
+            request_hash: RequestHash,
+            deviation_bps: u32,
+        },
+        /// A suspended feed was resumed by an admin.
+        PriceFeedResumed { request_hash: RequestHash },
+        /// The configuration overrides of a feed were set.
+        FeedConfigSet {
+            request_hash: RequestHash,
+            config: FeedConfig,
+        },
+        /// The configuration overrides of a feed were cleared, it uses the global values again.
+        FeedConfigCleared { request_hash: RequestHash },
+        /// A feed was added to the registry.
+        FeedRegistered { request_hash: RequestHash },
+        /// The metadata of a registered feed was replaced.
+        FeedMetadataUpdated { request_hash: RequestHash },
+        /// A feed was removed from the registry.
+        FeedDeregistered { request_hash: RequestHash },
     }
 
     #[pallet::error]
@@ -107,6 +302,40 @@ pub mod pallet {
         InvalidPayload,
         /// Not enough valid sources.
         NotEnoughValidSources,
+        /// The payload timestamp is further in the future than the max future drift.
+        TimestampInFuture,
+        /// The payload timestamp is older than the max submission age.
+        TimestampTooOld,
+        /// The price feed is not suspended.
+        PriceFeedNotSuspended,
+        /// The feed is already registered.
+        FeedAlreadyRegistered,
+        /// The feed is not registered.
+        FeedNotRegistered,
+        /// The feed metadata does not have a label for each price with decimals.
+        InvalidFeedMetadata,
+        /// The update is bound to another deployment, or is a legacy payload while a signing domain
+        /// is required.
+        InvalidSigningDomain,
+        /// The payload is past its `valid_until` timestamp.
+        PayloadExpired,
+    }
+
+    impl<T> From<UpdateRejectionReason> for Error<T> {
+        fn from(reason: UpdateRejectionReason) -> Self {
+            match reason {
+                UpdateRejectionReason::NotEnoughValidSignatures => {
+                    Error::<T>::NotEnoughValidSignatures
+                }
+                UpdateRejectionReason::InvalidPayload => Error::<T>::InvalidPayload,
+                UpdateRejectionReason::NotEnoughValidSources => Error::<T>::NotEnoughValidSources,
+                UpdateRejectionReason::TimestampInFuture => Error::<T>::TimestampInFuture,
+                UpdateRejectionReason::TimestampTooOld => Error::<T>::TimestampTooOld,
+                UpdateRejectionReason::FeedNotRegistered => Error::<T>::FeedNotRegistered,
+                UpdateRejectionReason::PayloadExpired => Error::<T>::PayloadExpired,
+                UpdateRejectionReason::InvalidSigningDomain => Error::<T>::InvalidSigningDomain,
+            }
+        }
     }
 
     #[pallet::hooks]
@@ -114,88 +343,127 @@ pub mod pallet {
         fn on_runtime_upgrade() -> frame_support::weights::Weight {
             crate::migration::migrate::<T>()
         }
+
+        #[cfg(feature = "try-runtime")]
+        fn pre_upgrade() -> Result<Vec<u8>, frame_support::sp_runtime::TryRuntimeError> {
+            crate::migration::pre_upgrade::<T>()
+        }
+
+        #[cfg(feature = "try-runtime")]
+        fn post_upgrade(state: Vec<u8>) -> Result<(), frame_support::sp_runtime::TryRuntimeError> {
+            crate::migration::post_upgrade::<T>(state)
+        }
+
+        fn integrity_test() {
+            assert!(
+                SIGNING_DOMAIN_TAG.len()
+                    + SigningDomain::max_encoded_len()
+                    + VersionedPricePayloadFor::<T>::max_encoded_len()
+                    <= T::MaxPayloadLen::get() as usize,
+                "MaxPayloadLen must fit a signing domain and a payload with MaxPrices prices and MaxCertificates certificates"
+            );
+        }
     }
 
     #[pallet::call]
     impl<T: Config> Pallet<T> {
         #[pallet::call_index(0)]
-        #[pallet::weight(< T as Config >::WeightInfo::update_price_feeds(update_data.len() as u32))]
+        #[pallet::weight(Pallet::<T>::update_price_feeds_weight(
+            T::WeightInfo::update_price_feeds,
+            update_data,
+            signatures,
+        ))]
         pub fn update_price_feeds(
             origin: OriginFor<T>,
-            update_data: BoundedVec<Vec<u8>, T::MaxPriceUpdates>,
-            signatures: BoundedVec<Vec<T::Signature>, T::MaxPriceUpdates>,
+            update_data: BoundedVec<UpdateDataFor<T>, T::MaxPriceUpdates>,
+            signatures: BoundedVec<UpdateSignaturesFor<T>, T::MaxPriceUpdates>,
         ) -> DispatchResultWithPostInfo {
             let _ = ensure_signed(origin)?;
-            ensure!(
-                signatures.len() >= update_data.len(),
-                Error::<T>::NotEnoughValidSignatures
-            );
+            Self::do_update_price_feeds(T::WeightInfo::update_price_feeds, update_data, signatures)
+        }
 
-            for (i, data) in update_data.into_iter().enumerate() {
-                // 1. check the signatures
-                let mut valid_signers_counter = 0u8;
-                let signatures_to_check = &signatures[i];
-                let message_hash = sp_io::hashing::blake2_256(data.as_slice());
-                let signers_threshold = Self::valid_signers_threshold();
-                for signature in signatures_to_check {
-                    if let Some(signer_pk) = signature.recover_prehashed(&message_hash) {
-                        let signer_account_id: T::AccountId = signer_pk.into_account();
-                        let is_trusted = Self::trusted_signer(&signer_account_id).is_some();
-                        if is_trusted {
-                            valid_signers_counter = valid_signers_counter.saturating_add(1);
-                            if valid_signers_counter >= signers_threshold {
-                                break;
-                            }
-                        }
-                    }
-                }
+        /// Same as [`Pallet::update_price_feeds`], but submitted as an unsigned transaction.
+        ///
+        /// The updates are fully checked by [`ValidateUnsigned`] before entering the pool,
+        /// so anyone can relay them without paying fees.
+        #[pallet::call_index(6)]
+        #[pallet::weight((
+            Pallet::<T>::update_price_feeds_weight(
+                T::WeightInfo::submit_price_feeds_unsigned,
+                update_data,
+                signatures,
+            ),
+            DispatchClass::Normal,
+            Pays::No,
+        ))]
+        pub fn submit_price_feeds_unsigned(
+            origin: OriginFor<T>,
+            update_data: BoundedVec<UpdateDataFor<T>, T::MaxPriceUpdates>,
+            signatures: BoundedVec<UpdateSignaturesFor<T>, T::MaxPriceUpdates>,
+        ) -> DispatchResultWithPostInfo {
+            ensure_none(origin)?;
+            let mut post_info = Self::do_update_price_feeds(
+                T::WeightInfo::submit_price_feeds_unsigned,
+                update_data,
+                signatures,
+            )?;
+            post_info.pays_fee = Pays::No;
+            Ok(post_info)
+        }
 
-                ensure!(
-                    valid_signers_counter >= signers_threshold,
-                    Error::<T>::NotEnoughValidSignatures
-                );
+        /// Same as [`Pallet::update_price_feeds`], but applies every valid update of the batch.
+        ///
+        /// Each invalid update is skipped with a [`Event::PriceFeedRejected`], the result of every update
+        /// is reported in a final [`Event::PriceFeedsProcessed`].
+        #[pallet::call_index(7)]
+        #[pallet::weight(Pallet::<T>::update_price_feeds_weight(
+            T::WeightInfo::try_update_price_feeds,
+            update_data,
+            signatures,
+        ))]
+        pub fn try_update_price_feeds(
+            origin: OriginFor<T>,
+            update_data: BoundedVec<UpdateDataFor<T>, T::MaxPriceUpdates>,
+            signatures: BoundedVec<UpdateSignaturesFor<T>, T::MaxPriceUpdates>,
+        ) -> DispatchResultWithPostInfo {
+            let _ = ensure_signed(origin)?;
 
-                // 2. decode the data
-                let price_payload =
-                    PricePayload::<T::MaxPrices, T::MaxCertificates>::decode(&mut data.as_slice())
-                        .map_err(|_| Error::<T>::InvalidPayload)?;
-
-                // 3. check certificates
-                let mut valid_sources_counter = 0u8;
-                let sources_threshold = Self::valid_sources_threshold();
-                for certificate in &price_payload.certificates {
-                    let is_valid_certificate = Self::certificate_trust_store(certificate).is_some();
-                    if is_valid_certificate {
-                        valid_sources_counter = valid_sources_counter.saturating_add(1);
-                        if valid_sources_counter >= sources_threshold {
-                            break;
+            let mut counters = UpdateCounters::default();
+            let mut results = Vec::with_capacity(update_data.len());
+            for (i, data) in update_data.into_iter().enumerate() {
+                let signatures_to_check = signatures
+                    .get(i)
+                    .map(|signatures| signatures.as_slice())
+                    .unwrap_or_default();
+                let result =
+                    match Self::verify_price_update(&data, signatures_to_check, &mut counters) {
+                        Ok(price_payload) => {
+                            let result = Self::set_price(price_payload);
+                            counters.record(result);
+                            result
                         }
-                    }
-                }
-
-                ensure!(
-                    valid_sources_counter >= sources_threshold,
-                    Error::<T>::NotEnoughValidSources
-                );
-
-                // 4. set the new price
-                let is_more_recent = Self::price_feed(price_payload.request_hash)
-                    .map(|current_price| current_price.timestamp < price_payload.timestamp)
-                    .unwrap_or(true);
-                if is_more_recent {
-                    <PriceFeed<T>>::insert(
-                        price_payload.request_hash,
-                        PriceEntry {
-                            timestamp: price_payload.timestamp,
-                            prices: price_payload.prices,
-                        },
-                    );
-
-                    Self::deposit_event(Event::<T>::PriceFeedUpdate(price_payload.request_hash));
-                }
+                        Err(reason) => {
+                            Self::deposit_event(Event::<T>::PriceFeedRejected {
+                                index: i as u32,
+                                request_hash: VersionedPricePayloadFor::<T>::decode_from(
+                                    SigningDomain::split(&data).1,
+                                )
+                                .ok()
+                                .map(|price_payload| price_payload.request_hash()),
+                                reason,
+                            });
+                            PriceUpdateResult::Rejected(reason)
+                        }
+                    };
+                results.push(result);
             }
 
-            Ok(().into())
+            Self::deposit_event(Event::<T>::PriceFeedsProcessed { results });
+            Ok(Some(Self::actual_update_price_feeds_weight(
+                T::WeightInfo::try_update_price_feeds,
+                &counters,
+            ))
+            .into())
         }
 
         #[pallet::call_index(1)]
@@ -204,7 +472,7 @@ pub mod pallet {
             origin: OriginFor<T>,
             new_threshold: u8,
         ) -> DispatchResultWithPostInfo {
-            ensure_root(origin)?;
+            T::AdminOrigin::ensure_origin(origin)?;
             <ValidSignersThreshold<T>>::put(new_threshold);
             Self::deposit_event(Event::<T>::SignersThresholdUpdated(new_threshold));
             Ok(().into())
@@ -216,7 +484,7 @@ pub mod pallet {
             origin: OriginFor<T>,
             new_threshold: u8,
         ) -> DispatchResultWithPostInfo {
-            ensure_root(origin)?;
+            T::AdminOrigin::ensure_origin(origin)?;
             <ValidSourcesThreshold<T>>::put(new_threshold);
             Self::deposit_event(Event::<T>::SourcesThresholdUpdated(new_threshold));
             Ok(().into())
@@ -228,19 +496,73 @@ pub mod pallet {
             origin: OriginFor<T>,
             new_time_period: u64,
         ) -> DispatchResultWithPostInfo {
-            ensure_root(origin)?;
+            T::AdminOrigin::ensure_origin(origin)?;
             <ValidTimePeriod<T>>::put(new_time_period);
             Self::deposit_event(Event::<T>::ValidTimePeriodUpdated(new_time_period));
             Ok(().into())
         }
 
+        #[pallet::call_index(10)]
+        #[pallet::weight(< T as Config >::WeightInfo::update_max_future_drift())]
+        pub fn update_max_future_drift(
+            origin: OriginFor<T>,
+            max_future_drift: Option<u64>,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <MaxFutureDrift<T>>::set(max_future_drift);
+            Self::deposit_event(Event::<T>::MaxFutureDriftUpdated(max_future_drift));
+            Ok(().into())
+        }
+
+        #[pallet::call_index(11)]
+        #[pallet::weight(< T as Config >::WeightInfo::update_max_submission_age())]
+        pub fn update_max_submission_age(
+            origin: OriginFor<T>,
+            max_submission_age: Option<u64>,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <MaxSubmissionAge<T>>::set(max_submission_age);
+            Self::deposit_event(Event::<T>::MaxSubmissionAgeUpdated(max_submission_age));
+            Ok(().into())
+        }
+
+        /// Enables or disables the rejection of updates whose feed is not in the registry.
+        #[pallet::call_index(17)]
+        #[pallet::weight(< T as Config >::WeightInfo::update_registered_feeds_only())]
+        pub fn update_registered_feeds_only(
+            origin: OriginFor<T>,
+            registered_feeds_only: bool,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <RegisteredFeedsOnly<T>>::put(registered_feeds_only);
+            Self::deposit_event(Event::<T>::RegisteredFeedsOnlyUpdated(
+                registered_feeds_only,
+            ));
+            Ok(().into())
+        }
+
+        /// Enables or disables the rejection of legacy payloads, not bound to this chain by a signing domain.
+        #[pallet::call_index(18)]
+        #[pallet::weight(< T as Config >::WeightInfo::update_require_signing_domain())]
+        pub fn update_require_signing_domain(
+            origin: OriginFor<T>,
+            require_signing_domain: bool,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <RequireSigningDomain<T>>::put(require_signing_domain);
+            Self::deposit_event(Event::<T>::RequireSigningDomainUpdated(
+                require_signing_domain,
+            ));
+            Ok(().into())
+        }
+
         #[pallet::call_index(4)]
         #[pallet::weight(< T as Config >::WeightInfo::update_trusted_signer())]
         pub fn update_trusted_signer(
             origin: OriginFor<T>,
             update: TrustedSignerUpdate<T::AccountId>,
         ) -> DispatchResultWithPostInfo {
-            ensure_root(origin)?;
+            T::SignerManagerOrigin::ensure_origin(origin)?;
             match &update.operation {
                 ListUpdateOperation::Add => {
                     <TrustedSigner<T>>::insert(&update.item, ());
@@ -259,7 +581,7 @@ pub mod pallet {
             origin: OriginFor<T>,
             update: CertificateTrustStoreUpdate,
         ) -> DispatchResultWithPostInfo {
-            ensure_root(origin)?;
+            T::CertificateManagerOrigin::ensure_origin(origin)?;
             match &update.operation {
                 ListUpdateOperation::Add => {
                     <CertificateTrustStore<T>>::insert(update.item, ());
@@ -271,5 +593,662 @@ pub mod pallet {
             Self::deposit_event(Event::<T>::CertificateTrustStoreUpdated(update));
             Ok(().into())
         }
+
+        /// Sets or removes the deviation guard checked before storing a new price of `request_hash`.
+        #[pallet::call_index(8)]
+        #[pallet::weight(< T as Config >::WeightInfo::set_deviation_guard())]
+        pub fn set_deviation_guard(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+            guard: Option<DeviationGuard>,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <DeviationGuards<T>>::set(request_hash, guard);
+            Self::deposit_event(Event::<T>::DeviationGuardUpdated {
+                request_hash,
+                guard,
+            });
+            Ok(().into())
+        }
+
+        /// Confirms the latest price of a feed suspended by its deviation guard.
+        #[pallet::call_index(9)]
+        #[pallet::weight(< T as Config >::WeightInfo::resume_price_feed())]
+        pub fn resume_price_feed(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                <SuspendedFeeds<T>>::take(request_hash).is_some(),
+                Error::<T>::PriceFeedNotSuspended
+            );
+            Self::deposit_event(Event::<T>::PriceFeedResumed { request_hash });
+            Ok(().into())
+        }
+
+        /// Overrides the global thresholds and valid time period for `request_hash`.
+        #[pallet::call_index(12)]
+        #[pallet::weight(< T as Config >::WeightInfo::set_feed_config())]
+        pub fn set_feed_config(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+            config: FeedConfig,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <FeedConfigs<T>>::insert(request_hash, config);
+            Self::deposit_event(Event::<T>::FeedConfigSet {
+                request_hash,
+                config,
+            });
+            Ok(().into())
+        }
+
+        /// Removes the overrides of `request_hash`, which falls back to the global configuration.
+        #[pallet::call_index(13)]
+        #[pallet::weight(< T as Config >::WeightInfo::clear_feed_config())]
+        pub fn clear_feed_config(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            <FeedConfigs<T>>::remove(request_hash);
+            Self::deposit_event(Event::<T>::FeedConfigCleared { request_hash });
+            Ok(().into())
+        }
+
+        /// Adds `request_hash` to the feed registry with its metadata.
+        #[pallet::call_index(14)]
+        #[pallet::weight(< T as Config >::WeightInfo::register_feed())]
+        pub fn register_feed(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+            metadata: FeedMetadataFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(metadata.is_consistent(), Error::<T>::InvalidFeedMetadata);
+            ensure!(
+                !<FeedRegistry<T>>::contains_key(request_hash),
+                Error::<T>::FeedAlreadyRegistered
+            );
+            <FeedRegistry<T>>::insert(request_hash, metadata);
+            Self::deposit_event(Event::<T>::FeedRegistered { request_hash });
+            Ok(().into())
+        }
+
+        /// Replaces the metadata of the registered feed `request_hash`.
+        #[pallet::call_index(15)]
+        #[pallet::weight(< T as Config >::WeightInfo::update_feed_metadata())]
+        pub fn update_feed_metadata(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+            metadata: FeedMetadataFor<T>,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(metadata.is_consistent(), Error::<T>::InvalidFeedMetadata);
+            ensure!(
+                <FeedRegistry<T>>::contains_key(request_hash),
+                Error::<T>::FeedNotRegistered
+            );
+            <FeedRegistry<T>>::insert(request_hash, metadata);
+            Self::deposit_event(Event::<T>::FeedMetadataUpdated { request_hash });
+            Ok(().into())
+        }
+
+        /// Removes `request_hash` from the feed registry, its prices are kept.
+        #[pallet::call_index(16)]
+        #[pallet::weight(< T as Config >::WeightInfo::deregister_feed())]
+        pub fn deregister_feed(
+            origin: OriginFor<T>,
+            request_hash: RequestHash,
+        ) -> DispatchResultWithPostInfo {
+            T::AdminOrigin::ensure_origin(origin)?;
+            ensure!(
+                <FeedRegistry<T>>::take(request_hash).is_some(),
+                Error::<T>::FeedNotRegistered
+            );
+            Self::deposit_event(Event::<T>::FeedDeregistered { request_hash });
+            Ok(().into())
+        }
+    }
+
+    #[pallet::validate_unsigned]
+    impl<T: Config> ValidateUnsigned for Pallet<T> {
+        type Call = Call<T>;
+
+        fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+            if let Call::submit_price_feeds_unsigned {
+                update_data,
+                signatures,
+            } = call
+            {
+                if signatures.len() < update_data.len() {
+                    return InvalidTransaction::BadProof.into();
+                }
+
+                let mut valid_transaction = ValidTransaction::with_tag_prefix("AcelonOracle")
+                    .longevity(T::UnsignedLongevity::get())
+                    .propagate(true);
+                let mut newest_timestamp = 0u64;
+                for (data, signatures_to_check) in update_data.iter().zip(signatures.iter()) {
+                    let price_payload = Self::verify_price_update(
+                        data,
+                        signatures_to_check,
+                        &mut UpdateCounters::default(),
+                    )
+                    .map_err(|reason| match reason {
+                        UpdateRejectionReason::InvalidPayload
+                        | UpdateRejectionReason::FeedNotRegistered => InvalidTransaction::Call,
+                        UpdateRejectionReason::TimestampInFuture => InvalidTransaction::Future,
+                        UpdateRejectionReason::TimestampTooOld
+                        | UpdateRejectionReason::PayloadExpired => InvalidTransaction::Stale,
+                        UpdateRejectionReason::NotEnoughValidSignatures
+                        | UpdateRejectionReason::NotEnoughValidSources
+                        | UpdateRejectionReason::InvalidSigningDomain => {
+                            InvalidTransaction::BadProof
+                        }
+                    })?;
+                    if !Self::is_more_recent(&price_payload) {
+                        return InvalidTransaction::Stale.into();
+                    }
+                    newest_timestamp = newest_timestamp.max(price_payload.timestamp());
+                    valid_transaction = valid_transaction.and_provides((
+                        price_payload.request_hash(),
+                        price_payload.timestamp(),
+                        price_payload.sequence(),
+                    ));
+                }
+
+                // fresher payloads get a higher priority, one step less per second of age
+                let age_in_seconds = Self::now().saturating_sub(newest_timestamp) / 1_000;
+                valid_transaction
+                    .priority(T::UnsignedPriority::get().saturating_sub(age_in_seconds))
+                    .build()
+            } else {
+                InvalidTransaction::Call.into()
+            }
+        }
+    }
+
+    impl<T: Config> Pallet<T> {
+        fn do_update_price_feeds(
+            weight: UpdatePriceFeedsWeight,
+            update_data: BoundedVec<UpdateDataFor<T>, T::MaxPriceUpdates>,
+            signatures: BoundedVec<UpdateSignaturesFor<T>, T::MaxPriceUpdates>,
+        ) -> DispatchResultWithPostInfo {
+            let mut counters = UpdateCounters::default();
+            ensure!(
+                signatures.len() >= update_data.len(),
+                Error::<T>::NotEnoughValidSignatures
+                    .with_weight(Self::actual_update_price_feeds_weight(weight, &counters))
+            );
+
+            for (i, data) in update_data.into_iter().enumerate() {
+                let price_payload = Self::verify_price_update(&data, &signatures[i], &mut counters)
+                    .map_err(|reason| {
+                        Error::<T>::from(reason)
+                            .with_weight(Self::actual_update_price_feeds_weight(weight, &counters))
+                    })?;
+                counters.record(Self::set_price(price_payload));
+            }
+
+            Ok(Some(Self::actual_update_price_feeds_weight(weight, &counters)).into())
+        }
+
+        /// The weight charged before dispatching a batch of updates with one of the `WeightInfo`
+        /// functions of the update extrinsics.
+        ///
+        /// Every update is assumed to recover as many signatures as the update carrying the most, to list
+        /// `MaxCertificates` certificates and to be written.
+        pub fn update_price_feeds_weight(
+            weight: UpdatePriceFeedsWeight,
+            update_data: &[UpdateDataFor<T>],
+            signatures: &[UpdateSignaturesFor<T>],
+        ) -> Weight {
+            weight(
+                update_data.len() as u32,
+                signatures
+                    .iter()
+                    .take(update_data.len())
+                    .map(|signatures_to_check| signatures_to_check.len() as u32)
+                    .max()
+                    .unwrap_or(0),
+                T::MaxCertificates::get(),
+            )
+        }
+
+        /// The weight of the work actually performed while processing a batch of updates.
+        ///
+        /// The processed updates are charged as if each recovered as many signatures and checked as
+        /// many certificates as the most demanding of them, and was written. An update rejected after
+        /// its verification deposits a `PriceFeedRejected` event instead of the writes of its feed, so
+        /// it is covered by that charge, while the writes an update skipped as not more recent did not
+        /// perform are refunded.
+        fn actual_update_price_feeds_weight(
+            weight: UpdatePriceFeedsWeight,
+            counters: &UpdateCounters,
+        ) -> Weight {
+            let skipped_writes = T::WeightInfo::set_price(1)
+                .saturating_sub(T::WeightInfo::set_price(0))
+                .saturating_mul(counters.skipped.into());
+            weight(counters.updates, counters.signatures, counters.certificates)
+                .saturating_sub(skipped_writes)
+        }
+
+        /// Checks the signatures and the certificates of a single update and returns its decoded payload.
+        pub(crate) fn verify_price_update(
+            data: &[u8],
+            signatures_to_check: &[T::Signature],
+            counters: &mut UpdateCounters,
+        ) -> Result<VersionedPricePayloadFor<T>, UpdateRejectionReason> {
+            counters.updates += 1;
+
+            // the signed data has to be bound to this chain, unless legacy payloads are accepted
+            let (signing_domain, payload_data) = SigningDomain::split(data);
+            match signing_domain {
+                Some(signing_domain) => ensure!(
+                    signing_domain == Self::signing_domain(),
+                    UpdateRejectionReason::InvalidSigningDomain
+                ),
+                None => ensure!(
+                    !Self::require_signing_domain(),
+                    UpdateRejectionReason::InvalidSigningDomain
+                ),
+            }
+
+            // the payload is decoded upfront to apply the configuration of its feed,
+            // but it is only rejected as invalid once the signatures are checked
+            let decoded_payload = VersionedPricePayloadFor::<T>::decode_from(payload_data);
+            let feed_config = decoded_payload
+                .as_ref()
+                .ok()
+                .and_then(|price_payload| Self::feed_config(price_payload.request_hash()))
+                .unwrap_or_default();
+
+            // 1. check the signatures, a signer is counted once however many times it signed
+            let mut valid_signers = BTreeSet::<T::AccountId>::new();
+            let message_hash = sp_io::hashing::blake2_256(data);
+            let signers_threshold = feed_config
+                .valid_signers_threshold
+                .unwrap_or_else(Self::valid_signers_threshold)
+                as usize;
+            for (i, signature) in signatures_to_check.iter().enumerate() {
+                counters.signatures = counters.signatures.max(i as u32 + 1);
+                if let Some(signer_pk) = signature.recover_prehashed(&message_hash) {
+                    let signer_account_id: T::AccountId = signer_pk.into_account();
+                    let is_trusted = Self::trusted_signer(&signer_account_id).is_some();
+                    if is_trusted {
+                        valid_signers.insert(signer_account_id);
+                        if valid_signers.len() >= signers_threshold {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            ensure!(
+                valid_signers.len() >= signers_threshold,
+                UpdateRejectionReason::NotEnoughValidSignatures
+            );
+
+            // 2. decode the data, check its feed is accepted and its timestamp against the chain time
+            let price_payload = decoded_payload
+                .ok()
+                .filter(|price_payload| price_payload.is_consistent())
+                .ok_or(UpdateRejectionReason::InvalidPayload)?;
+            if Self::registered_feeds_only() {
+                ensure!(
+                    <FeedRegistry<T>>::contains_key(price_payload.request_hash()),
+                    UpdateRejectionReason::FeedNotRegistered
+                );
+            }
+            let now = Self::now();
+            if let Some(max_future_drift) = Self::max_future_drift() {
+                ensure!(
+                    price_payload.timestamp() <= now.saturating_add(max_future_drift),
+                    UpdateRejectionReason::TimestampInFuture
+                );
+            }
+            if let Some(max_submission_age) = Self::max_submission_age() {
+                ensure!(
+                    now.saturating_sub(price_payload.timestamp()) <= max_submission_age,
+                    UpdateRejectionReason::TimestampTooOld
+                );
+            }
+            if let Some(valid_until) = price_payload.valid_until() {
+                ensure!(now <= valid_until, UpdateRejectionReason::PayloadExpired);
+            }
+
+            // 3. check certificates, a certificate is counted once however many times it is listed
+            let mut valid_sources = BTreeSet::<&Certificate>::new();
+            let sources_threshold = feed_config
+                .valid_sources_threshold
+                .unwrap_or_else(Self::valid_sources_threshold)
+                as usize;
+            for (i, certificate) in price_payload.certificates().iter().enumerate() {
+                counters.certificates = counters.certificates.max(i as u32 + 1);
+                let is_valid_certificate = Self::certificate_trust_store(certificate).is_some();
+                if is_valid_certificate {
+                    valid_sources.insert(certificate);
+                    if valid_sources.len() >= sources_threshold {
+                        break;
+                    }
+                }
+            }
+
+            ensure!(
+                valid_sources.len() >= sources_threshold,
+                UpdateRejectionReason::NotEnoughValidSources
+            );
+
+            Ok(price_payload)
+        }
+
+        /// Returns true if the payload is more recent than the stored price of its feed.
+        ///
+        /// Payloads with the same timestamp are ordered by their sequence number, V1 payloads having none.
+        pub(crate) fn is_more_recent(price_payload: &VersionedPricePayloadFor<T>) -> bool {
+            Self::price_feed(price_payload.request_hash())
+                .map(|current_price| {
+                    (current_price.timestamp, current_price.sequence)
+                        < (price_payload.timestamp(), price_payload.sequence())
+                })
+                .unwrap_or(true)
+        }
+
+        /// Stores the price of a verified payload if it is more recent than the current one
+        /// and passes the deviation guard of its feed.
+        pub(crate) fn set_price(price_payload: VersionedPricePayloadFor<T>) -> PriceUpdateResult {
+            // 4. set the new price
+            if !Self::is_more_recent(&price_payload) {
+                return PriceUpdateResult::NotMoreRecent;
+            }
+
+            let request_hash = price_payload.request_hash();
+            let price_entry = price_payload.into_price_entry();
+            if let Some(guard) = Self::deviation_guard(request_hash) {
+                let deviation_bps = Self::price_feed(request_hash)
+                    .map(|current_price| {
+                        crate::deviation_bps(&current_price.prices, &price_entry.prices)
+                    })
+                    .unwrap_or(0);
+                if deviation_bps > guard.max_deviation_bps {
+                    match guard.mode {
+                        DeviationMode::Reject => {
+                            Self::deposit_event(Event::<T>::PriceDeviationRejected {
+                                request_hash,
+                                deviation_bps,
+                            });
+                            return PriceUpdateResult::DeviationRejected;
+                        }
+                        DeviationMode::Suspend => {
+                            <SuspendedFeeds<T>>::insert(request_hash, ());
+                            Self::deposit_event(Event::<T>::PriceFeedSuspended {
+                                request_hash,
+                                deviation_bps,
+                            });
+                        }
+                    }
+                }
+            }
+
+            Self::record_observation(request_hash, &price_entry);
+            <PriceHistory<T>>::mutate(request_hash, |history| {
+                // the oldest entry is dropped once the history is full
+                let _ = history.force_insert_keep_left(
+                    0,
+                    PriceHistoryEntry {
+                        timestamp: price_entry.timestamp,
+                        prices: price_entry.prices.clone(),
+                        block_number: frame_system::Pallet::<T>::block_number(),
+                    },
+                );
+            });
+            <PriceFeed<T>>::insert(request_hash, price_entry);
+
+            Self::deposit_event(Event::<T>::PriceFeedUpdate(request_hash));
+            PriceUpdateResult::Updated
+        }
+
+        /// Adds an observation of the cumulative prices of a feed before its new price is stored.
+        ///
+        /// The observations restart when the number of prices of the feed changes.
+        fn record_observation(request_hash: RequestHash, price_entry: &PriceEntry<T::MaxPrices>) {
+            let prices_count = price_entry.prices.len();
+            let zeros = || BoundedVec::truncate_from(vec![0; prices_count]);
+            let current_price = Self::price_feed(request_hash)
+                .filter(|current_price| current_price.prices.len() == prices_count);
+
+            <PriceObservations<T>>::mutate(request_hash, |observations| {
+                let cumulatives = match current_price {
+                    Some(current_price) => {
+                        let last_cumulatives = match observations.first() {
+                            Some(last) if last.timestamp == current_price.timestamp => {
+                                last.cumulatives.clone()
+                            }
+                            _ => {
+                                // the feed was stored before the accumulator, start from its current price
+                                *observations = BoundedVec::truncate_from(vec![PriceObservation {
+                                    timestamp: current_price.timestamp,
+                                    cumulatives: zeros(),
+                                }]);
+                                zeros()
+                            }
+                        };
+                        let elapsed = price_entry
+                            .timestamp
+                            .saturating_sub(current_price.timestamp)
+                            as u128;
+                        BoundedVec::truncate_from(
+                            last_cumulatives
+                                .iter()
+                                .zip(current_price.prices.iter())
+                                .map(|(cumulative, price)| {
+                                    cumulative.wrapping_add(price.wrapping_mul(elapsed))
+                                })
+                                .collect(),
+                        )
+                    }
+                    None => {
+                        observations.clear();
+                        zeros()
+                    }
+                };
+                let _ = observations.force_insert_keep_left(
+                    0,
+                    PriceObservation {
+                        timestamp: price_entry.timestamp,
+                        cumulatives,
+                    },
+                );
+            });
+        }
+
+        /// The signing domain binding updates to this chain, see [`SigningDomain`].
+        pub fn signing_domain() -> SigningDomain {
+            let genesis_hash = frame_system::Pallet::<T>::block_hash(BlockNumberFor::<T>::zero());
+            let mut target = [0u8; 32];
+            let len = genesis_hash.as_ref().len().min(target.len());
+            target[..len].copy_from_slice(&genesis_hash.as_ref()[..len]);
+            SigningDomain::new(target)
+        }
+
+        /// The valid time period of `request_hash`, its override or the global one.
+        pub fn feed_valid_time_period(request_hash: &RequestHash) -> u64 {
+            Self::feed_config(request_hash)
+                .and_then(|config| config.valid_time_period)
+                .unwrap_or_else(Self::valid_time_period)
+        }
+
+        /// Returns true if a price was ever set for `request_hash`.
+        pub fn price_feed_exists(request_hash: RequestHash) -> bool {
+            Self::price_feed(request_hash)
+                .map(|value| value.timestamp > 0)
+                .unwrap_or(false)
+        }
+
+        /// Returns the price for `request_hash` if it is not older than [`ValidTimePeriod`].
+        pub fn get_price(request_hash: RequestHash) -> Option<PriceEntry<T::MaxPrices>> {
+            Self::get_price_no_older_than(request_hash, Self::feed_valid_time_period(&request_hash))
+        }
+
+        /// Returns the price for `request_hash` if it is not older than `age` milliseconds.
+        pub fn get_price_no_older_than(
+            request_hash: RequestHash,
+            age: u64,
+        ) -> Option<PriceEntry<T::MaxPrices>> {
+            <Self as PriceProvider<RequestHash>>::price_no_older_than(&request_hash, age).ok()
+        }
+
+        /// Returns the time-weighted average of the price at `index` of `request_hash` over the last `window` milliseconds.
+        pub fn twap(
+            request_hash: RequestHash,
+            index: u32,
+            window: u64,
+        ) -> Result<u128, PriceError> {
+            <Self as PriceProvider<RequestHash>>::twap(&request_hash, index, window)
+        }
+
+        /// Returns up to `limit` price feeds in storage order, starting after `start_after`.
+        pub fn list_feeds(
+            start_after: Option<RequestHash>,
+            limit: u32,
+        ) -> Vec<(RequestHash, PriceEntry<T::MaxPrices>)> {
+            let iter = match start_after {
+                Some(key) => <PriceFeed<T>>::iter_from(<PriceFeed<T>>::hashed_key_for(key)),
+                None => <PriceFeed<T>>::iter(),
+            };
+            iter.take(limit as usize).collect()
+        }
+
+        /// Returns up to `limit` registered feeds with their metadata, starting after the `start_after` key.
+        pub fn registered_feeds(
+            start_after: Option<RequestHash>,
+            limit: u32,
+        ) -> Vec<(RequestHash, FeedInfo)> {
+            let iter = match start_after {
+                Some(key) => <FeedRegistry<T>>::iter_from(<FeedRegistry<T>>::hashed_key_for(key)),
+                None => <FeedRegistry<T>>::iter(),
+            };
+            iter.take(limit as usize)
+                .map(|(request_hash, metadata)| (request_hash, metadata.into()))
+                .collect()
+        }
+
+        /// Returns up to the `n` most recent prices stored for `request_hash`, newest first.
+        ///
+        /// The latest price is included, so it is the first entry when the feed has a price.
+        pub fn price_history(request_hash: RequestHash, n: u32) -> Vec<PriceHistoryEntryFor<T>> {
+            let mut history = <PriceHistory<T>>::get(request_hash).into_inner();
+            history.truncate(n as usize);
+            history
+        }
+
+        /// Returns the feeds of the [`Event::PriceFeedUpdate`]s deposited in the current block, in
+        /// the order they were first updated, with their price stored at the end of the block.
+        ///
+        /// Reads all the events of the block, do not use it in on-chain logic.
+        pub fn price_feed_updates() -> Vec<(RequestHash, PriceEntry<T::MaxPrices>)>
+        where
+            <T as Config>::RuntimeEvent: TryInto<Event<T>>,
+        {
+            let mut updated_feeds = BTreeSet::new();
+            frame_system::Pallet::<T>::read_events_no_consensus()
+                .filter_map(|record| {
+                    let event: <T as Config>::RuntimeEvent = record.event.into();
+                    match event.try_into() {
+                        Ok(Event::<T>::PriceFeedUpdate(request_hash))
+                            if updated_feeds.insert(request_hash) =>
+                        {
+                            Self::price_feed(request_hash)
+                                .map(|price_entry| (request_hash, price_entry))
+                        }
+                        _ => None,
+                    }
+                })
+                .collect()
+        }
+
+        /// The current time in milliseconds, the same unit used by price payloads.
+        pub(crate) fn now() -> u64 {
+            T::UnixTime::now().as_millis() as u64
+        }
+    }
+
+    impl<T: Config> PriceProvider<RequestHash> for Pallet<T> {
+        type PriceEntry = PriceEntry<T::MaxPrices>;
+
+        fn price(request_hash: &RequestHash) -> Result<Self::PriceEntry, PriceError> {
+            Self::price_no_older_than(request_hash, Self::feed_valid_time_period(request_hash))
+        }
+
+        fn price_no_older_than(
+            request_hash: &RequestHash,
+            age: u64,
+        ) -> Result<Self::PriceEntry, PriceError> {
+            let price_feed = Self::price_feed(request_hash).ok_or(PriceError::NotFound)?;
+            ensure!(
+                !<SuspendedFeeds<T>>::contains_key(request_hash),
+                PriceError::Suspended
+            );
+            let now = Self::now();
+            ensure!(
+                now.saturating_sub(price_feed.timestamp) <= age && !price_feed.is_expired(now),
+                PriceError::Stale
+            );
+            Ok(price_feed)
+        }
+
+        fn price_at_index(request_hash: &RequestHash, index: u32) -> Result<u128, PriceError> {
+            let price_feed = <Self as PriceProvider<RequestHash>>::price(request_hash)?;
+            price_feed
+                .prices
+                .get(index as usize)
+                .copied()
+                .ok_or(PriceError::IndexOutOfRange)
+        }
+
+        fn twap(request_hash: &RequestHash, index: u32, window: u64) -> Result<u128, PriceError> {
+            let price_feed = <Self as PriceProvider<RequestHash>>::price(request_hash)?;
+            let index = index as usize;
+            let price = *price_feed
+                .prices
+                .get(index)
+                .ok_or(PriceError::IndexOutOfRange)?;
+            if window == 0 {
+                return Ok(price);
+            }
+
+            let observations = <PriceObservations<T>>::get(request_hash);
+            // the cumulative price at `timestamp`, which is after the observation at `position`
+            let cumulative_at = |position: usize, timestamp: u64| -> Option<u128> {
+                let observation = observations.get(position)?;
+                let cumulative = *observation.cumulatives.get(index)?;
+                // the price stored with an observation is constant until the next (newer) one
+                let observed_price = match position.checked_sub(1) {
+                    Some(next) => {
+                        let next = observations.get(next)?;
+                        next.cumulatives.get(index)?.wrapping_sub(cumulative)
+                            / next.timestamp.saturating_sub(observation.timestamp).max(1) as u128
+                    }
+                    None => price,
+                };
+                let elapsed = timestamp.saturating_sub(observation.timestamp) as u128;
+                Some(cumulative.wrapping_add(observed_price.wrapping_mul(elapsed)))
+            };
+
+            let end = Self::now().max(price_feed.timestamp);
+            let start = end
+                .checked_sub(window)
+                .ok_or(PriceError::InsufficientHistory)?;
+            let start_position = observations
+                .iter()
+                .position(|observation| observation.timestamp <= start)
+                .ok_or(PriceError::InsufficientHistory)?;
+            let end_cumulative = cumulative_at(0, end).ok_or(PriceError::InsufficientHistory)?;
+            let start_cumulative =
+                cumulative_at(start_position, start).ok_or(PriceError::InsufficientHistory)?;
+            Ok(end_cumulative.wrapping_sub(start_cumulative) / window as u128)
+        }
     }
 }
@@ -1,12 +1,20 @@
 use frame_support::{
+    storage::bounded_vec::BoundedVec,
     traits::{Get, GetStorageVersion, StorageVersion},
     weights::Weight,
 };
+use scale::{Decode, Encode};
+#[cfg(feature = "try-runtime")]
+use {
+    frame_support::{ensure, sp_runtime::TryRuntimeError},
+    sp_std::vec::Vec,
+};
 
 use super::*;
+use crate::types::PriceEntry;
 
 pub fn migrate<T: Config>() -> Weight {
-    let migrations: [(u16, &dyn Fn() -> Weight); 0] = [];
+    let migrations: [(u16, &dyn Fn() -> Weight); 1] = [(2, &v2::migrate::<T>)];
 
     let on_chain_version = Pallet::<T>::on_chain_storage_version();
     let mut weight: Weight = Default::default();
@@ -19,3 +27,52 @@ pub fn migrate<T: Config>() -> Weight {
     STORAGE_VERSION.put::<Pallet<T>>();
     weight + T::DbWeight::get().writes(1)
 }
+
+/// Records the number of price entries, which must not change during the migration.
+#[cfg(feature = "try-runtime")]
+pub fn pre_upgrade<T: Config>() -> Result<Vec<u8>, TryRuntimeError> {
+    // the keys are counted as the entries may not decode before the migration
+    Ok((<PriceFeed<T>>::iter_keys().count() as u64).encode())
+}
+
+/// Checks that every price entry was kept and decodes with the current layout.
+#[cfg(feature = "try-runtime")]
+pub fn post_upgrade<T: Config>(state: Vec<u8>) -> Result<(), TryRuntimeError> {
+    let entries = u64::decode(&mut &state[..]).map_err(|_| "invalid pre-upgrade state")?;
+    ensure!(
+        <PriceFeed<T>>::iter().count() as u64 == entries,
+        "the migration lost price entries"
+    );
+    ensure!(
+        Pallet::<T>::on_chain_storage_version() == STORAGE_VERSION,
+        "the storage version was not updated"
+    );
+    Ok(())
+}
+
+/// Adds the fields of the V2 price payloads to the stored price entries.
+mod v2 {
+    use super::*;
+
+    #[derive(Encode, Decode)]
+    struct OldPriceEntry<MaxPrices: Get<u32>> {
+        timestamp: u64,
+        prices: BoundedVec<u128, MaxPrices>,
+    }
+
+    pub fn migrate<T: Config>() -> Weight {
+        let mut translated = 0u64;
+        <PriceFeed<T>>::translate::<OldPriceEntry<T::MaxPrices>, _>(|_, old| {
+            translated += 1;
+            Some(PriceEntry {
+                timestamp: old.timestamp,
+                prices: old.prices,
+                decimals: Default::default(),
+                confidences: Default::default(),
+                valid_until: None,
+                sequence: None,
+            })
+        });
+        T::DbWeight::get().reads_writes(translated, translated)
+    }
+}
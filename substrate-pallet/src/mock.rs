@@ -2,8 +2,11 @@ use frame_support::{
     derive_impl,
     sp_runtime::{traits::IdentityLookup, AccountId32, BuildStorage},
     traits::{ConstU16, ConstU32, ConstU64},
+    weights::constants::RocksDbWeight,
 };
+use frame_system::EnsureRoot;
 use sp_core::H256;
+use sp_keystore::{testing::MemoryKeystore, KeystoreExt};
 
 use crate::types::CU32;
 
@@ -14,6 +17,13 @@ pub struct ExtBuilder;
 
 impl ExtBuilder {
     pub fn build(self) -> sp_io::TestExternalities {
+        self.build_with_genesis(Default::default())
+    }
+
+    pub fn build_with_genesis(
+        self,
+        acelon_config: crate::GenesisConfig<Test>,
+    ) -> sp_io::TestExternalities {
         let mut t = frame_system::GenesisConfig::<Test>::default()
             .build_storage()
             .unwrap();
@@ -29,7 +39,11 @@ impl ExtBuilder {
         )
         .unwrap();
 
+        <crate::GenesisConfig<Test> as BuildStorage>::assimilate_storage(&acelon_config, &mut t)
+            .unwrap();
+
         let mut ext = sp_io::TestExternalities::new(t);
+        ext.register_extension(KeystoreExt::new(MemoryKeystore::new()));
         ext.execute_with(|| System::set_block_number(1));
         ext
     }
@@ -45,7 +59,8 @@ frame_support::construct_runtime!(
     pub enum Test {
         System: frame_system::{Pallet, Call, Config<T>, Storage, Event<T>} = 0,
         ParachainInfo: parachain_info::{Pallet, Storage, Config<T>},
-        Acelon: crate::{Pallet, Call, Storage, Event<T>}
+        Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent},
+        Acelon: crate::{Pallet, Call, Config<T>, Storage, Event<T>, ValidateUnsigned}
     }
 );
 
@@ -59,7 +74,7 @@ impl frame_system::Config for Test {
     type BlockHashCount = ConstU64<250>;
     type Version = ();
     type AccountData = ();
-    type DbWeight = ();
+    type DbWeight = RocksDbWeight;
     type BlockWeights = ();
     type BlockLength = ();
     type SS58Prefix = ConstU16<42>;
@@ -69,12 +84,30 @@ impl frame_system::Config for Test {
 
 impl parachain_info::Config for Test {}
 
+impl pallet_timestamp::Config for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = ConstU64<1>;
+    type WeightInfo = ();
+}
+
 impl crate::Config for Test {
     type RuntimeEvent = RuntimeEvent;
     type MaxPrices = CU32<50>;
     type MaxCertificates = CU32<50>;
     type MaxPriceUpdates = CU32<10>;
+    type MaxSignaturesPerUpdate = CU32<16>;
+    type MaxPayloadLen = CU32<4096>;
+    type MaxHistory = CU32<10>;
+    type MaxSymbolLen = CU32<32>;
+    type MaxDescriptionLen = CU32<256>;
     type Signature = crate::types::Signature;
     type Public = crate::types::Public;
+    type UnixTime = Timestamp;
+    type UnsignedPriority = ConstU64<{ u64::MAX / 2 }>;
+    type UnsignedLongevity = ConstU64<5>;
+    type AdminOrigin = EnsureRoot<AccountId>;
+    type SignerManagerOrigin = EnsureRoot<AccountId>;
+    type CertificateManagerOrigin = EnsureRoot<AccountId>;
     type WeightInfo = ();
 }
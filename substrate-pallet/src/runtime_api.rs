@@ -0,0 +1,62 @@
+use frame_support::{sp_runtime::traits::NumberFor, traits::Get};
+use scale::Codec;
+use sp_std::prelude::*;
+
+use crate::types::{
+    FeedConfig, FeedInfo, PriceEntry, PriceError, PriceHistoryEntry, RequestHash, SigningDomain,
+};
+
+sp_api::decl_runtime_apis! {
+    /// Runtime API to read validated prices and the oracle configuration.
+    ///
+    /// `MaxPrices` has to be set to the `MaxPrices` bound the pallet is configured with,
+    /// see [`crate::types::MaxPricesFor`].
+    pub trait AcelonOracleApi<MaxPrices>
+    where
+        MaxPrices: Get<u32> + Codec,
+    {
+        /// Returns the price of a feed if it is not older than its valid time period.
+        fn get_price(request_hash: RequestHash) -> Option<PriceEntry<MaxPrices>>;
+
+        /// Returns the price of a feed if it is not older than `age` milliseconds.
+        fn get_price_no_older_than(request_hash: RequestHash, age: u64) -> Option<PriceEntry<MaxPrices>>;
+
+        /// Returns true if a price was ever set for the feed.
+        fn price_feed_exists(request_hash: RequestHash) -> bool;
+
+        fn valid_signers_threshold() -> u8;
+
+        fn valid_sources_threshold() -> u8;
+
+        fn valid_time_period() -> u64;
+
+        /// Returns the signing domain binding price updates to this chain.
+        fn signing_domain() -> SigningDomain;
+
+        /// Returns the configuration overrides of a feed, if any.
+        fn feed_config(request_hash: RequestHash) -> Option<FeedConfig>;
+
+        /// Returns up to `limit` stored price feeds, starting after the `start_after` key.
+        ///
+        /// The order is the storage order, so the last returned key is the cursor for the next page.
+        fn list_feeds(start_after: Option<RequestHash>, limit: u32) -> Vec<(RequestHash, PriceEntry<MaxPrices>)>;
+
+        /// Returns the metadata of a feed if it is registered.
+        fn feed_metadata(request_hash: RequestHash) -> Option<FeedInfo>;
+
+        /// Returns up to `limit` registered feeds with their metadata, starting after the `start_after` key.
+        fn registered_feeds(start_after: Option<RequestHash>, limit: u32) -> Vec<(RequestHash, FeedInfo)>;
+
+        /// Returns up to the `n` most recent prices of a feed with the block they were stored in, newest first.
+        fn price_history(request_hash: RequestHash, n: u32) -> Vec<PriceHistoryEntry<MaxPrices, NumberFor<Block>>>;
+
+        /// Returns the time-weighted average of the price at `index` of a feed over the last `window` milliseconds.
+        fn twap(request_hash: RequestHash, index: u32, window: u64) -> Result<u128, PriceError>;
+
+        /// Returns the feeds updated in the block the call is executed at, with their price stored at
+        /// the end of the block.
+        ///
+        /// Reads the system events, so it is only meant to be called off-chain.
+        fn price_feed_updates() -> Vec<(RequestHash, PriceEntry<MaxPrices>)>;
+    }
+}
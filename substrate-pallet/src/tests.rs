@@ -1,9 +1,112 @@
 #![cfg(test)]
 
-use frame_support::{assert_ok, sp_runtime::AccountId32};
+use frame_support::{
+    assert_err_ignore_postinfo, assert_noop, assert_ok,
+    pallet_prelude::{InvalidTransaction, TransactionSource, ValidateUnsigned},
+    sp_runtime::{AccountId32, DispatchError},
+    traits::Get,
+    BoundedVec,
+};
 use hex_literal::hex;
+use scale::{Decode, Encode};
+use sp_core::{ecdsa, Pair};
 
-use crate::{mock::*, types::*};
+use crate::{
+    mock::*,
+    traits::{PriceProvider, WeightInfo},
+    types::*,
+    Error,
+};
+
+const PAYLOAD: [u8; 154] = hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+const SIGNATURE: [u8; 65] = hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b");
+const PAYLOAD_TIMESTAMP: u64 = 1_728_394_897_587;
+const REQUEST_HASH: RequestHash =
+    hex!("dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc");
+
+fn oracle() -> AccountId {
+    sp_io::hashing::blake2_256(
+        hex!("03b689085552418111b9c8a6683e470c83f16f79a96c8f5fe35df1a3ab0e24d020").as_slice(),
+    )
+    .into()
+}
+
+fn signer_account(pair: &ecdsa::Pair) -> AccountId {
+    sp_io::hashing::blake2_256(pair.public().as_ref()).into()
+}
+
+fn sign(pair: &ecdsa::Pair, payload: &[u8]) -> Signature {
+    let signature = pair.sign_prehashed(&sp_io::hashing::blake2_256(payload));
+    <[u8; 65]>::try_from(signature.as_ref()).unwrap().into()
+}
+
+/// Bounds the update data or the signatures of a batch of price updates.
+fn batch<T: std::fmt::Debug, S: Get<u32>, B: Get<u32>>(
+    items: Vec<Vec<T>>,
+) -> BoundedVec<BoundedVec<T, S>, B> {
+    items
+        .into_iter()
+        .map(|item| item.try_into().unwrap())
+        .collect::<Vec<_>>()
+        .try_into()
+        .unwrap()
+}
+
+/// Encodes a payload for the `REQUEST_HASH` feed, listing a single trusted certificate.
+fn price_payload(prices: Vec<u128>, timestamp: u64) -> Vec<u8> {
+    PricePayloadFor::<Test> {
+        prices: prices.try_into().unwrap(),
+        timestamp,
+        certificates: vec![hex!(
+            "ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e"
+        )]
+        .try_into()
+        .unwrap(),
+        request_hash: REQUEST_HASH,
+    }
+    .encode()
+}
+
+fn add_trusted_signer(signer: AccountId) {
+    assert_ok!(Acelon::update_trusted_signer(
+        RuntimeOrigin::root(),
+        TrustedSignerUpdate {
+            operation: ListUpdateOperation::Add,
+            item: signer,
+        },
+    ));
+}
+
+fn setup_oracle() {
+    add_trusted_signer(oracle());
+
+    assert_ok!(Acelon::update_certificate_trust_store(
+        RuntimeOrigin::root(),
+        CertificateTrustStoreUpdate {
+            operation: ListUpdateOperation::Add,
+            item: hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e")
+        }
+    ));
+
+    assert_ok!(Acelon::update_certificate_trust_store(
+        RuntimeOrigin::root(),
+        CertificateTrustStoreUpdate {
+            operation: ListUpdateOperation::Add,
+            item: hex!("4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384")
+        }
+    ));
+
+    assert_ok!(Acelon::update_certificate_trust_store(
+        RuntimeOrigin::root(),
+        CertificateTrustStoreUpdate {
+            operation: ListUpdateOperation::Add,
+            item: hex!("b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80")
+        }
+    ));
+
+    assert_ok!(Acelon::update_signers_threshold(RuntimeOrigin::root(), 1));
+    assert_ok!(Acelon::update_sources_threshold(RuntimeOrigin::root(), 1));
+}
 
 #[test]
 fn test_update_price_feeds() {
@@ -49,8 +152,956 @@ fn test_update_price_feeds() {
 
         assert_ok!(Acelon::update_price_feeds(
             RuntimeOrigin::signed(AccountId32::new([0u8; 32])), 
-            vec![hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc").to_vec()].try_into().unwrap(),
-            vec![vec![hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b").into()]].try_into().unwrap(),
+            batch(vec![hex!("0440cf669e0e0000000000000000000000b3285d6c920100000cef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e4795062d13e1ed971c6b6e5699764681e4d090bad39a7ef367cc9cb705652384b75b095dc5d2a59f082ef17d0c64104aaeab321bb0a49a26b9b27a59792fce80dd450ee6601d873d2d381d738d87a131f5605ec30c6adbc9138b79f2dfd6f3dc").to_vec()]),
+            batch(vec![vec![hex!("147124904bd67756d1b5efecd302e01c87639cab15a6b05f5913877c0d55c5787b676599461f0da5fe4c3fd02981a1216cd226b767c2f0ef464bbed26c72fa221b").into()]]),
+        ));
+    });
+}
+
+#[test]
+fn test_get_price() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_valid_time_period(
+            RuntimeOrigin::root(),
+            60_000
+        ));
+        assert!(!Acelon::price_feed_exists(REQUEST_HASH));
+
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+        assert!(Acelon::price_feed_exists(REQUEST_HASH));
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 30_000);
+        let price = Acelon::get_price(REQUEST_HASH).unwrap();
+        assert_eq!(price.timestamp, PAYLOAD_TIMESTAMP);
+        assert!(Acelon::get_price_no_older_than(REQUEST_HASH, 10_000).is_none());
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 60_001);
+        assert!(Acelon::get_price(REQUEST_HASH).is_none());
+    });
+}
+
+#[test]
+fn test_price_provider() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_valid_time_period(
+            RuntimeOrigin::root(),
+            60_000
+        ));
+        assert_eq!(Acelon::price(&REQUEST_HASH), Err(PriceError::NotFound));
+
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 30_000);
+        assert_eq!(Acelon::price_at_index(&REQUEST_HASH, 0), Ok(62_787_080_000));
+        assert_eq!(
+            Acelon::price_at_index(&REQUEST_HASH, 1),
+            Err(PriceError::IndexOutOfRange)
+        );
+        assert_eq!(
+            Acelon::price_no_older_than(&REQUEST_HASH, 10_000),
+            Err(PriceError::Stale)
+        );
+    });
+}
+
+#[test]
+fn test_price_history() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+        assert!(Acelon::price_history(REQUEST_HASH, 10).is_empty());
+
+        for i in 0..12u64 {
+            System::set_block_number(i + 1);
+            let payload = price_payload(vec![i as u128], PAYLOAD_TIMESTAMP + i);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ));
+        }
+
+        let history = Acelon::price_history(REQUEST_HASH, 2);
+        assert_eq!(
+            history,
+            vec![
+                PriceHistoryEntry {
+                    timestamp: PAYLOAD_TIMESTAMP + 11,
+                    prices: vec![11].try_into().unwrap(),
+                    block_number: 12,
+                },
+                PriceHistoryEntry {
+                    timestamp: PAYLOAD_TIMESTAMP + 10,
+                    prices: vec![10].try_into().unwrap(),
+                    block_number: 11,
+                },
+            ]
+        );
+
+        // only the last `MaxHistory` prices are kept
+        let history = Acelon::price_history(REQUEST_HASH, 100);
+        assert_eq!(history.len(), 10);
+        assert_eq!(history[9].timestamp, PAYLOAD_TIMESTAMP + 2);
+    });
+}
+
+#[test]
+fn test_price_feed_updates() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+
+        for i in 0..2u64 {
+            let payload = price_payload(vec![i as u128], PAYLOAD_TIMESTAMP + i);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ));
+        }
+
+        // a feed updated twice is reported once, with the price stored at the end of the block
+        System::assert_has_event(crate::Event::<Test>::PriceFeedUpdate(REQUEST_HASH).into());
+        let updates = Acelon::price_feed_updates();
+        assert_eq!(updates.len(), 1);
+        assert_eq!(updates[0].0, REQUEST_HASH);
+        assert_eq!(updates[0].1.timestamp, PAYLOAD_TIMESTAMP + 1);
+        assert_eq!(updates[0].1.prices.to_vec(), vec![1]);
+
+        System::set_block_number(2);
+        System::reset_events();
+        assert!(Acelon::price_feed_updates().is_empty());
+    });
+}
+
+#[test]
+fn test_twap() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_valid_time_period(
+            RuntimeOrigin::root(),
+            60_000
         ));
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+
+        for (i, price) in [100, 200, 400].into_iter().enumerate() {
+            let payload = price_payload(vec![price], PAYLOAD_TIMESTAMP + i as u64 * 1_000);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ));
+        }
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 3_000);
+
+        assert_eq!(Acelon::twap(REQUEST_HASH, 0, 0), Ok(400));
+        assert_eq!(Acelon::twap(REQUEST_HASH, 0, 2_000), Ok(300));
+        assert_eq!(Acelon::twap(REQUEST_HASH, 0, 2_500), Ok(260));
+        assert_eq!(Acelon::twap(REQUEST_HASH, 0, 3_000), Ok(233));
+        assert_eq!(
+            Acelon::twap(REQUEST_HASH, 0, 3_001),
+            Err(PriceError::InsufficientHistory)
+        );
+        assert_eq!(
+            Acelon::twap(REQUEST_HASH, 1, 1_000),
+            Err(PriceError::IndexOutOfRange)
+        );
+    });
+}
+
+#[test]
+fn test_deviation_guard_rejects_update() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+        let guard = DeviationGuard {
+            max_deviation_bps: 1_000,
+            mode: DeviationMode::Reject,
+        };
+        assert_ok!(Acelon::set_deviation_guard(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            Some(guard)
+        ));
+
+        for (i, price) in [1_000, 1_100, 2_000].into_iter().enumerate() {
+            let payload = price_payload(vec![price], PAYLOAD_TIMESTAMP + i as u64);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ));
+        }
+
+        System::assert_last_event(
+            crate::Event::<Test>::PriceDeviationRejected {
+                request_hash: REQUEST_HASH,
+                deviation_bps: 8_181,
+            }
+            .into(),
+        );
+        let price_feed = Acelon::price_feed(REQUEST_HASH).unwrap();
+        assert_eq!(price_feed.prices.into_inner(), vec![1_100]);
+    });
+}
+
+#[test]
+fn test_deviation_guard_suspends_feed() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_valid_time_period(
+            RuntimeOrigin::root(),
+            60_000
+        ));
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+        let guard = DeviationGuard {
+            max_deviation_bps: 1_000,
+            mode: DeviationMode::Suspend,
+        };
+        assert_ok!(Acelon::set_deviation_guard(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            Some(guard)
+        ));
+        assert_noop!(
+            Acelon::resume_price_feed(RuntimeOrigin::root(), REQUEST_HASH),
+            Error::<Test>::PriceFeedNotSuspended
+        );
+
+        for (i, price) in [1_000, 100].into_iter().enumerate() {
+            let payload = price_payload(vec![price], PAYLOAD_TIMESTAMP + i as u64);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ));
+        }
+        System::assert_has_event(
+            crate::Event::<Test>::PriceFeedSuspended {
+                request_hash: REQUEST_HASH,
+                deviation_bps: 9_000,
+            }
+            .into(),
+        );
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 1_000);
+        assert_eq!(Acelon::price(&REQUEST_HASH), Err(PriceError::Suspended));
+        assert!(Acelon::get_price(REQUEST_HASH).is_none());
+
+        assert_noop!(
+            Acelon::resume_price_feed(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                REQUEST_HASH
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Acelon::resume_price_feed(
+            RuntimeOrigin::root(),
+            REQUEST_HASH
+        ));
+        assert_eq!(Acelon::price_at_index(&REQUEST_HASH, 0), Ok(100));
+    });
+}
+
+#[test]
+fn test_payload_timestamp_must_be_admissible() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_max_future_drift(
+            RuntimeOrigin::root(),
+            Some(5_000)
+        ));
+        assert_ok!(Acelon::update_max_submission_age(
+            RuntimeOrigin::root(),
+            Some(60_000)
+        ));
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP - 5_001);
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ),
+            Error::<Test>::TimestampInFuture
+        );
+        let call = crate::Call::<Test>::submit_price_feeds_unsigned {
+            update_data: batch(vec![PAYLOAD.to_vec()]),
+            signatures: batch(vec![vec![SIGNATURE.into()]]),
+        };
+        assert_eq!(
+            Acelon::validate_unsigned(TransactionSource::External, &call),
+            InvalidTransaction::Future.into()
+        );
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 60_001);
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ),
+            Error::<Test>::TimestampTooOld
+        );
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP - 5_000);
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+    });
+}
+
+#[test]
+fn test_feed_config_overrides_global_config() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let config = FeedConfig {
+            valid_signers_threshold: Some(2),
+            valid_sources_threshold: None,
+            valid_time_period: Some(60_000),
+        };
+        assert_noop!(
+            Acelon::set_feed_config(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                REQUEST_HASH,
+                config
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_ok!(Acelon::set_feed_config(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            config
+        ));
+        System::assert_last_event(
+            crate::Event::<Test>::FeedConfigSet {
+                request_hash: REQUEST_HASH,
+                config,
+            }
+            .into(),
+        );
+
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ),
+            Error::<Test>::NotEnoughValidSignatures
+        );
+
+        assert_ok!(Acelon::set_feed_config(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            FeedConfig {
+                valid_signers_threshold: Some(1),
+                ..config
+            }
+        ));
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 30_000);
+        assert!(Acelon::get_price(REQUEST_HASH).is_some());
+        assert_eq!(Acelon::price_at_index(&REQUEST_HASH, 0), Ok(62_787_080_000));
+
+        assert_ok!(Acelon::clear_feed_config(
+            RuntimeOrigin::root(),
+            REQUEST_HASH
+        ));
+        System::assert_last_event(
+            crate::Event::<Test>::FeedConfigCleared {
+                request_hash: REQUEST_HASH,
+            }
+            .into(),
+        );
+        assert!(Acelon::feed_config(REQUEST_HASH).is_none());
+        assert!(Acelon::get_price(REQUEST_HASH).is_none());
+    });
+}
+
+fn feed_metadata(labels: Vec<&[u8]>) -> FeedMetadataFor<Test> {
+    FeedMetadata {
+        symbol: BoundedVec::truncate_from(b"BTC/USD".to_vec()),
+        description: BoundedVec::truncate_from(b"Bitcoin price in US dollars".to_vec()),
+        decimals: BoundedVec::truncate_from(vec![6; labels.len()]),
+        labels: BoundedVec::truncate_from(
+            labels
+                .into_iter()
+                .map(|label| BoundedVec::truncate_from(label.to_vec()))
+                .collect(),
+        ),
+    }
+}
+
+#[test]
+fn test_feed_registry() {
+    ExtBuilder.build().execute_with(|| {
+        let metadata = feed_metadata(vec![b"mid"]);
+        assert_noop!(
+            Acelon::register_feed(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                REQUEST_HASH,
+                metadata.clone()
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Acelon::update_feed_metadata(RuntimeOrigin::root(), REQUEST_HASH, metadata.clone()),
+            Error::<Test>::FeedNotRegistered
+        );
+        let mut inconsistent = metadata.clone();
+        inconsistent.decimals = BoundedVec::truncate_from(vec![6, 6]);
+        assert_noop!(
+            Acelon::register_feed(RuntimeOrigin::root(), REQUEST_HASH, inconsistent),
+            Error::<Test>::InvalidFeedMetadata
+        );
+
+        assert_ok!(Acelon::register_feed(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            metadata.clone()
+        ));
+        System::assert_last_event(
+            crate::Event::<Test>::FeedRegistered {
+                request_hash: REQUEST_HASH,
+            }
+            .into(),
+        );
+        assert_noop!(
+            Acelon::register_feed(RuntimeOrigin::root(), REQUEST_HASH, metadata.clone()),
+            Error::<Test>::FeedAlreadyRegistered
+        );
+        assert_eq!(Acelon::feed_metadata(REQUEST_HASH), Some(metadata));
+
+        let metadata = feed_metadata(vec![b"bid", b"ask", b"mid"]);
+        assert_ok!(Acelon::update_feed_metadata(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            metadata.clone()
+        ));
+        assert_eq!(
+            Acelon::registered_feeds(None, 10),
+            vec![(REQUEST_HASH, metadata.into())]
+        );
+
+        assert_ok!(Acelon::deregister_feed(RuntimeOrigin::root(), REQUEST_HASH));
+        System::assert_last_event(
+            crate::Event::<Test>::FeedDeregistered {
+                request_hash: REQUEST_HASH,
+            }
+            .into(),
+        );
+        assert!(Acelon::feed_metadata(REQUEST_HASH).is_none());
+        assert_noop!(
+            Acelon::deregister_feed(RuntimeOrigin::root(), REQUEST_HASH),
+            Error::<Test>::FeedNotRegistered
+        );
+    });
+}
+
+#[test]
+fn test_registered_feeds_only() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_registered_feeds_only(
+            RuntimeOrigin::root(),
+            true
+        ));
+
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ),
+            Error::<Test>::FeedNotRegistered
+        );
+        assert_ok!(Acelon::try_update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+        System::assert_has_event(
+            crate::Event::<Test>::PriceFeedRejected {
+                index: 0,
+                request_hash: Some(REQUEST_HASH),
+                reason: UpdateRejectionReason::FeedNotRegistered,
+            }
+            .into(),
+        );
+        assert!(!Acelon::price_feed_exists(REQUEST_HASH));
+
+        assert_ok!(Acelon::register_feed(
+            RuntimeOrigin::root(),
+            REQUEST_HASH,
+            feed_metadata(vec![b"mid"])
+        ));
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+        assert!(Acelon::price_feed_exists(REQUEST_HASH));
+    });
+}
+
+#[test]
+fn test_signing_domain() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+        let payload = price_payload(vec![1_000], PAYLOAD_TIMESTAMP);
+        let signing_domain = Acelon::signing_domain();
+        assert_eq!(signing_domain.version, SigningDomain::VERSION);
+        assert_eq!(signing_domain.target, System::block_hash(0).0);
+
+        let other_domain = SigningDomain::new([1u8; 32]).bind(&payload);
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![other_domain.clone()]),
+                batch(vec![vec![sign(&signer, &other_domain)]]),
+            ),
+            Error::<Test>::InvalidSigningDomain
+        );
+
+        // legacy payloads are accepted until a signing domain is required
+        assert_ok!(Acelon::update_require_signing_domain(
+            RuntimeOrigin::root(),
+            true
+        ));
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ),
+            Error::<Test>::InvalidSigningDomain
+        );
+
+        let data = signing_domain.bind(&payload);
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![data.clone()]),
+            batch(vec![vec![sign(&signer, &data)]]),
+        ));
+        assert_eq!(
+            Acelon::price_feed(REQUEST_HASH).unwrap().timestamp,
+            PAYLOAD_TIMESTAMP
+        );
+    });
+}
+
+/// Encodes a V2 payload for the `REQUEST_HASH` feed, listing a single trusted certificate.
+fn price_payload_v2(prices: Vec<u128>, timestamp: u64, valid_until: u64, sequence: u64) -> Vec<u8> {
+    VersionedPricePayloadFor::<Test>::V2(PricePayloadV2For::<Test> {
+        decimals: vec![8; prices.len()].try_into().unwrap(),
+        confidences: vec![10; prices.len()].try_into().unwrap(),
+        prices: prices.try_into().unwrap(),
+        timestamp,
+        valid_until,
+        sequence,
+        certificates: vec![hex!(
+            "ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e"
+        )]
+        .try_into()
+        .unwrap(),
+        request_hash: REQUEST_HASH,
+    })
+    .encode()
+}
+
+#[test]
+fn test_price_payload_v2() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_valid_time_period(
+            RuntimeOrigin::root(),
+            60_000
+        ));
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+        let update = |payload: Vec<u8>| {
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            )
+        };
+
+        // a V1 payload is decoded as before and stores no V2 fields
+        assert_ok!(update(PAYLOAD.to_vec()));
+        let price = Acelon::price_feed(REQUEST_HASH).unwrap();
+        assert!(price.decimals.is_empty());
+        assert_eq!(price.valid_until, None);
+
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 2_000);
+        assert_err_ignore_postinfo!(
+            update(price_payload_v2(
+                vec![1_000],
+                PAYLOAD_TIMESTAMP,
+                PAYLOAD_TIMESTAMP + 1_000,
+                0
+            )),
+            Error::<Test>::PayloadExpired
+        );
+
+        // the decimals and the confidences must match the prices
+        let mut inconsistent = VersionedPricePayloadFor::<Test>::decode_from(&price_payload_v2(
+            vec![1_000],
+            PAYLOAD_TIMESTAMP,
+            PAYLOAD_TIMESTAMP + 5_000,
+            0,
+        ))
+        .unwrap();
+        if let VersionedPricePayload::V2(payload) = &mut inconsistent {
+            payload.decimals = Default::default();
+        }
+        assert_err_ignore_postinfo!(update(inconsistent.encode()), Error::<Test>::InvalidPayload);
+
+        // with the same timestamp, a V2 payload is more recent than a V1 one, then by sequence
+        assert_ok!(update(price_payload_v2(
+            vec![1_000],
+            PAYLOAD_TIMESTAMP,
+            PAYLOAD_TIMESTAMP + 5_000,
+            1
+        )));
+        assert_ok!(update(price_payload_v2(
+            vec![1_001],
+            PAYLOAD_TIMESTAMP,
+            PAYLOAD_TIMESTAMP + 5_000,
+            1
+        )));
+        let price = Acelon::price_feed(REQUEST_HASH).unwrap();
+        assert_eq!(price.prices.to_vec(), vec![1_000]);
+        assert_eq!(price.decimals.to_vec(), vec![8]);
+        assert_eq!(price.confidences.to_vec(), vec![10]);
+        assert_eq!(price.valid_until, Some(PAYLOAD_TIMESTAMP + 5_000));
+        assert_eq!(price.sequence, Some(1));
+        assert_eq!(Acelon::price(&REQUEST_HASH), Ok(price));
+
+        // the price is stale past its expiry, whatever the valid time period
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 5_001);
+        assert_eq!(Acelon::price(&REQUEST_HASH), Err(PriceError::Stale));
+    });
+}
+
+#[test]
+fn test_genesis_config() {
+    ExtBuilder
+        .build_with_genesis(crate::GenesisConfig {
+            trusted_signers: vec![oracle()],
+            certificates: vec![hex!(
+                "ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e"
+            )],
+            valid_signers_threshold: 1,
+            valid_sources_threshold: 1,
+            valid_time_period: 60_000,
+        })
+        .execute_with(|| {
+            assert_eq!(Acelon::valid_time_period(), 60_000);
+            assert_ok!(Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into()]]),
+            ));
+            assert!(Acelon::price_feed_exists(REQUEST_HASH));
+        });
+}
+
+#[test]
+fn test_admin_calls_require_admin_origin() {
+    ExtBuilder.build().execute_with(|| {
+        let origin = RuntimeOrigin::signed(AccountId32::new([0u8; 32]));
+        assert_noop!(
+            Acelon::update_signers_threshold(origin.clone(), 1),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Acelon::update_trusted_signer(
+                origin.clone(),
+                TrustedSignerUpdate {
+                    operation: ListUpdateOperation::Add,
+                    item: oracle(),
+                },
+            ),
+            DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Acelon::update_certificate_trust_store(
+                origin,
+                CertificateTrustStoreUpdate {
+                    operation: ListUpdateOperation::Add,
+                    item: [0u8; 32],
+                },
+            ),
+            DispatchError::BadOrigin
+        );
+    });
+}
+
+#[test]
+fn test_submit_price_feeds_unsigned() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        Timestamp::set_timestamp(PAYLOAD_TIMESTAMP + 2_000);
+
+        let call = crate::Call::<Test>::submit_price_feeds_unsigned {
+            update_data: batch(vec![PAYLOAD.to_vec()]),
+            signatures: batch(vec![vec![SIGNATURE.into()]]),
+        };
+        let valid_transaction =
+            Acelon::validate_unsigned(TransactionSource::External, &call).unwrap();
+        assert_eq!(valid_transaction.priority, u64::MAX / 2 - 2);
+        assert_eq!(valid_transaction.provides.len(), 1);
+
+        assert_ok!(Acelon::submit_price_feeds_unsigned(
+            RuntimeOrigin::none(),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        ));
+        assert!(Acelon::price_feed_exists(REQUEST_HASH));
+
+        assert_eq!(
+            Acelon::validate_unsigned(TransactionSource::External, &call),
+            InvalidTransaction::Stale.into()
+        );
+    });
+}
+
+#[test]
+fn test_submit_price_feeds_unsigned_rejects_untrusted_signatures() {
+    ExtBuilder.build().execute_with(|| {
+        assert_ok!(Acelon::update_signers_threshold(RuntimeOrigin::root(), 1));
+
+        let call = crate::Call::<Test>::submit_price_feeds_unsigned {
+            update_data: batch(vec![PAYLOAD.to_vec()]),
+            signatures: batch(vec![vec![SIGNATURE.into()]]),
+        };
+        assert_eq!(
+            Acelon::validate_unsigned(TransactionSource::External, &call),
+            InvalidTransaction::BadProof.into()
+        );
+    });
+}
+
+#[test]
+fn test_duplicated_signatures_are_counted_once() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_signers_threshold(RuntimeOrigin::root(), 2));
+
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![PAYLOAD.to_vec()]),
+                batch(vec![vec![SIGNATURE.into(), SIGNATURE.into()]]),
+            ),
+            Error::<Test>::NotEnoughValidSignatures
+        );
+
+        let other_signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&other_signer));
+        assert_ok!(Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into(), sign(&other_signer, &PAYLOAD)]]),
+        ));
+    });
+}
+
+#[test]
+fn test_duplicated_certificates_are_counted_once() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        assert_ok!(Acelon::update_sources_threshold(RuntimeOrigin::root(), 2));
+        let signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+        add_trusted_signer(signer_account(&signer));
+
+        let certificate = hex!("ef27778d3c1f3546d7c49fe18aa7c2559a11171df720b9b4230dc2758bb2603e");
+        let payload = PricePayloadFor::<Test> {
+            prices: vec![1_000].try_into().unwrap(),
+            timestamp: PAYLOAD_TIMESTAMP,
+            certificates: vec![certificate, certificate].try_into().unwrap(),
+            request_hash: REQUEST_HASH,
+        }
+        .encode();
+
+        assert_err_ignore_postinfo!(
+            Acelon::update_price_feeds(
+                RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+                batch(vec![payload.clone()]),
+                batch(vec![vec![sign(&signer, &payload)]]),
+            ),
+            Error::<Test>::NotEnoughValidSources
+        );
+    });
+}
+
+#[test]
+fn test_try_update_price_feeds_applies_valid_updates() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+
+        assert_ok!(Acelon::try_update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![vec![1, 2, 3], PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()], vec![SIGNATURE.into()]]),
+        ));
+
+        assert!(Acelon::price_feed_exists(REQUEST_HASH));
+        System::assert_has_event(
+            crate::Event::<Test>::PriceFeedRejected {
+                index: 0,
+                request_hash: None,
+                reason: UpdateRejectionReason::NotEnoughValidSignatures,
+            }
+            .into(),
+        );
+        System::assert_last_event(
+            crate::Event::<Test>::PriceFeedsProcessed {
+                results: vec![
+                    PriceUpdateResult::Rejected(UpdateRejectionReason::NotEnoughValidSignatures),
+                    PriceUpdateResult::Updated,
+                ],
+            }
+            .into(),
+        );
+    });
+}
+
+#[test]
+fn test_update_price_feeds_returns_actual_weight() {
+    ExtBuilder.build().execute_with(|| {
+        setup_oracle();
+        let other_signer = ecdsa::Pair::from_seed(&[1u8; 32]);
+
+        let post_info = Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![sign(&other_signer, &PAYLOAD), SIGNATURE.into()]]),
+        )
+        .unwrap();
+
+        // both signatures are recovered, the first certificate already satisfies the threshold
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::update_price_feeds(1, 2, 1))
+        );
+
+        // the same update is skipped as not more recent, the writes it did not perform are refunded
+        let post_info = Acelon::update_price_feeds(
+            RuntimeOrigin::signed(AccountId32::new([0u8; 32])),
+            batch(vec![PAYLOAD.to_vec()]),
+            batch(vec![vec![SIGNATURE.into()]]),
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(
+                <() as WeightInfo>::update_price_feeds(1, 1, 1).saturating_sub(
+                    <() as WeightInfo>::set_price(1)
+                        .saturating_sub(<() as WeightInfo>::set_price(0))
+                )
+            )
+        );
+    });
+}
+
+#[test]
+fn test_update_price_feeds_rejects_oversized_updates() {
+    let max_payload_len = <Test as crate::Config>::MaxPayloadLen::get() as usize;
+    let max_signatures = <Test as crate::Config>::MaxSignaturesPerUpdate::get() as usize;
+    let decode_call = |update_data: Vec<Vec<u8>>, signatures: Vec<Vec<Signature>>| {
+        crate::Call::<Test>::decode(&mut &(0u8, update_data, signatures).encode()[..])
+    };
+
+    assert!(decode_call(
+        vec![vec![0; max_payload_len]],
+        vec![vec![SIGNATURE.into(); max_signatures]]
+    )
+    .is_ok());
+    assert!(decode_call(
+        vec![vec![0; max_payload_len + 1]],
+        vec![vec![SIGNATURE.into()]]
+    )
+    .is_err());
+    assert!(decode_call(
+        vec![PAYLOAD.to_vec()],
+        vec![vec![SIGNATURE.into(); max_signatures + 1]]
+    )
+    .is_err());
+}
+
+#[test]
+fn test_migration_to_v2() {
+    use frame_support::{
+        storage::unhashed,
+        traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+    };
+
+    ExtBuilder.build().execute_with(|| {
+        // price entries of V1, before the fields of the V2 payloads
+        let old_entries = [
+            ([1u8; 32], 1_000u64, vec![1u128, 2]),
+            ([2u8; 32], 2_000, vec![3]),
+        ];
+        for (request_hash, timestamp, prices) in old_entries.iter() {
+            unhashed::put(
+                &crate::PriceFeed::<Test>::hashed_key_for(request_hash),
+                &(timestamp, prices),
+            );
+        }
+        StorageVersion::new(1).put::<Acelon>();
+
+        #[cfg(feature = "try-runtime")]
+        let state = crate::migration::pre_upgrade::<Test>().unwrap();
+
+        let weight = <Acelon as OnRuntimeUpgrade>::on_runtime_upgrade();
+
+        #[cfg(feature = "try-runtime")]
+        assert_ok!(crate::migration::post_upgrade::<Test>(state));
+
+        // each entry is read and written, then the storage version is written
+        assert_eq!(
+            weight,
+            <Test as frame_system::Config>::DbWeight::get().reads_writes(2, 3)
+        );
+        assert_eq!(Acelon::on_chain_storage_version(), StorageVersion::new(2));
+        for (request_hash, timestamp, prices) in old_entries {
+            assert_eq!(
+                Acelon::price_feed(request_hash),
+                Some(PriceEntry {
+                    timestamp,
+                    prices: prices.try_into().unwrap(),
+                    decimals: Default::default(),
+                    confidences: Default::default(),
+                    valid_until: None,
+                    sequence: None,
+                })
+            );
+        }
     });
 }
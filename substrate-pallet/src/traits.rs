@@ -1,6 +1,8 @@
-use frame_support::{sp_runtime::traits::Saturating, traits::Get, weights::Weight};
+use frame_support::{traits::Get, weights::Weight};
 use sp_std::{fmt, prelude::*};
 
+use crate::types::PriceError;
+
 /// A bound that can be used to restrict length sequence types such as [`frame_support::BoundedVec`] appearing in types used in dispatchable functions.
 ///
 /// Similar to [`frame_support::Parameter`] without encoding traits, since bounds are never encoded.
@@ -13,37 +15,57 @@ pub trait RecoverableSignature {
     fn recover_prehashed(&self, message: &[u8; 32]) -> Option<Self::Public>;
 }
 
-pub trait WeightInfo {
-    fn update_price_feeds(updates: u32) -> Weight;
-    fn update_signers_threshold() -> Weight;
-    fn update_sources_threshold() -> Weight;
-    fn update_valid_time_period() -> Weight;
-    fn update_trusted_signer() -> Weight;
-    fn update_certificate_trust_store() -> Weight;
-}
+/// Read access to validated prices, meant to be used as a bound in the `Config` of other pallets.
+pub trait PriceProvider<RequestHash> {
+    type PriceEntry;
 
-impl WeightInfo for () {
-    fn update_price_feeds(updates: u32) -> Weight {
-        Weight::from_parts(10_000.saturating_mul(updates as u64), 0)
-    }
+    /// Returns the price of a feed if it is not older than the configured valid time period.
+    fn price(request_hash: &RequestHash) -> Result<Self::PriceEntry, PriceError>;
 
-    fn update_signers_threshold() -> Weight {
-        Weight::from_parts(10_000, 0)
-    }
+    /// Returns the price of a feed if it is not older than `age` milliseconds.
+    fn price_no_older_than(
+        request_hash: &RequestHash,
+        age: u64,
+    ) -> Result<Self::PriceEntry, PriceError>;
 
-    fn update_sources_threshold() -> Weight {
-        Weight::from_parts(10_000, 0)
-    }
+    /// Returns a single price of a feed if it is not older than the configured valid time period.
+    fn price_at_index(request_hash: &RequestHash, index: u32) -> Result<u128, PriceError>;
 
-    fn update_valid_time_period() -> Weight {
-        Weight::from_parts(10_000, 0)
-    }
+    /// Returns the time-weighted average of a single price of a feed over the last `window` milliseconds.
+    ///
+    /// The latest price has to be valid like for [`PriceProvider::price`].
+    fn twap(request_hash: &RequestHash, index: u32, window: u64) -> Result<u128, PriceError>;
+}
 
-    fn update_trusted_signer() -> Weight {
-        Weight::from_parts(10_000, 0)
-    }
+/// A [`WeightInfo`] function of an update extrinsic, taking the updates of the batch, and the signatures
+/// and the certificates of each update.
+pub type UpdatePriceFeedsWeight = fn(u32, u32, u32) -> Weight;
 
-    fn update_certificate_trust_store() -> Weight {
-        Weight::from_parts(10_000, 0)
-    }
+pub trait WeightInfo {
+    /// Processing a batch of `updates` updates of `MaxPayloadLen` bytes, each recovering `signatures`
+    /// signatures and checking `certificates` certificates before overwriting its feed.
+    fn update_price_feeds(updates: u32, signatures: u32, certificates: u32) -> Weight;
+    /// Same as [`WeightInfo::update_price_feeds`], also depositing the results of the batch.
+    fn try_update_price_feeds(updates: u32, signatures: u32, certificates: u32) -> Weight;
+    /// Same as [`WeightInfo::update_price_feeds`], dispatched as an unsigned transaction.
+    fn submit_price_feeds_unsigned(updates: u32, signatures: u32, certificates: u32) -> Weight;
+    /// Storing the price of a verified update: `written` is 1 if the update overwrites its feed, 0 if it
+    /// is skipped as not more recent.
+    fn set_price(written: u32) -> Weight;
+    fn update_signers_threshold() -> Weight;
+    fn update_sources_threshold() -> Weight;
+    fn update_valid_time_period() -> Weight;
+    fn update_max_future_drift() -> Weight;
+    fn update_max_submission_age() -> Weight;
+    fn update_registered_feeds_only() -> Weight;
+    fn update_require_signing_domain() -> Weight;
+    fn update_trusted_signer() -> Weight;
+    fn update_certificate_trust_store() -> Weight;
+    fn set_deviation_guard() -> Weight;
+    fn resume_price_feed() -> Weight;
+    fn set_feed_config() -> Weight;
+    fn clear_feed_config() -> Weight;
+    fn register_feed() -> Weight;
+    fn update_feed_metadata() -> Weight;
+    fn deregister_feed() -> Weight;
 }
@@ -1,22 +1,108 @@
 use frame_support::{
     sp_runtime::traits::IdentifyAccount, storage::bounded_vec::BoundedVec, traits::Get,
 };
+use frame_system::pallet_prelude::BlockNumberFor;
 use scale::{Decode, Encode, MaxEncodedLen};
 use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use sp_core::{crypto::AccountId32, ecdsa, RuntimeDebug, TypedGet};
+use sp_std::prelude::*;
 
 use crate::traits::RecoverableSignature;
 
 pub type Certificate = [u8; 32];
 pub type RequestHash = [u8; 32];
 
-#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq)]
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct PriceEntry<MaxPrices: Get<u32>> {
     pub timestamp: u64,
     pub prices: BoundedVec<u128, MaxPrices>,
+    /// The decimals of each price, empty for prices of a V1 payload.
+    pub decimals: BoundedVec<u8, MaxPrices>,
+    /// The confidence of each price, empty for prices of a V1 payload.
+    pub confidences: BoundedVec<u128, MaxPrices>,
+    /// The price is stale after this timestamp, `None` for prices of a V1 payload.
+    pub valid_until: Option<u64>,
+    /// The sequence number of the payload, `None` for prices of a V1 payload.
+    pub sequence: Option<u64>,
+}
+
+impl<MaxPrices: Get<u32>> PriceEntry<MaxPrices> {
+    /// Returns true if the price has an expiry before `now`.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.valid_until
+            .map(|valid_until| valid_until < now)
+            .unwrap_or(false)
+    }
+}
+
+/// A price stored for a feed, with the block it was stored in.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct PriceHistoryEntry<MaxPrices: Get<u32>, BlockNumber> {
+    pub timestamp: u64,
+    pub prices: BoundedVec<u128, MaxPrices>,
+    pub block_number: BlockNumber,
+}
+
+/// A snapshot of the cumulative prices of a feed, used to compute time-weighted average prices.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq)]
+pub struct PriceObservation<MaxPrices: Get<u32>> {
+    /// The timestamp of the price stored with this observation.
+    pub timestamp: u64,
+    /// For each price index, the sum of `price * elapsed milliseconds` up to `timestamp`,
+    /// wrapping on overflow. Only differences between two observations are meaningful.
+    pub cumulatives: BoundedVec<u128, MaxPrices>,
+}
+
+/// The bytes starting the signed data of an update bound to a [`SigningDomain`].
+pub const SIGNING_DOMAIN_TAG: [u8; 6] = *b"acelon";
+
+/// Binds the signed data of an update to a single chain, so it cannot be replayed on other deployments
+/// trusting the same signers.
+///
+/// Bound data is `SIGNING_DOMAIN_TAG ++ SigningDomain ++ PricePayload`, SCALE encoded, and the signatures
+/// cover all of it. Data without the tag is a legacy payload.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct SigningDomain {
+    pub version: u8,
+    /// The genesis hash of the chain.
+    pub target: [u8; 32],
+}
+
+impl SigningDomain {
+    /// The version of the signing domain accepted by the pallet.
+    pub const VERSION: u8 = 1;
+
+    pub fn new(target: [u8; 32]) -> Self {
+        Self {
+            version: Self::VERSION,
+            target,
+        }
+    }
+
+    /// Splits signed data into its signing domain, `None` for a legacy payload, and its payload.
+    pub fn split(data: &[u8]) -> (Option<Self>, &[u8]) {
+        if let Some(mut rest) = data.strip_prefix(SIGNING_DOMAIN_TAG.as_slice()) {
+            if let Ok(signing_domain) = Self::decode(&mut rest) {
+                return (Some(signing_domain), rest);
+            }
+        }
+        (None, data)
+    }
+
+    /// Returns the data to sign to bind `payload` to this domain.
+    pub fn bind(&self, payload: &[u8]) -> Vec<u8> {
+        let mut data = SIGNING_DOMAIN_TAG.to_vec();
+        self.encode_to(&mut data);
+        data.extend_from_slice(payload);
+        data
+    }
 }
 
 #[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq)]
@@ -29,6 +115,289 @@ pub struct PricePayload<MaxPrices: Get<u32>, MaxCertificates: Get<u32>> {
     pub request_hash: RequestHash,
 }
 
+/// The bytes starting an encoded [`PricePayloadV2`].
+///
+/// `0xff` can't start a V1 payload, as it would announce a number of prices that does not fit a `u32`.
+pub const PRICE_PAYLOAD_V2_MAGIC: [u8; 4] = [0xff, b'a', b'p', 2];
+
+/// The second version of the price payload, encoded after [`PRICE_PAYLOAD_V2_MAGIC`].
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct PricePayloadV2<MaxPrices: Get<u32>, MaxCertificates: Get<u32>> {
+    pub prices: BoundedVec<u128, MaxPrices>,
+    /// The decimals of each price.
+    pub decimals: BoundedVec<u8, MaxPrices>,
+    /// The confidence of each price, in the unit of the price.
+    pub confidences: BoundedVec<u128, MaxPrices>,
+    pub timestamp: u64,
+    /// The payload is rejected, and its price stale, after this timestamp.
+    pub valid_until: u64,
+    /// Orders the payloads of a feed with the same timestamp.
+    pub sequence: u64,
+    pub certificates: BoundedVec<Certificate, MaxCertificates>,
+    pub request_hash: RequestHash,
+}
+
+/// A price payload of any supported version.
+#[derive(RuntimeDebug, Clone, PartialEq)]
+pub enum VersionedPricePayload<MaxPrices: Get<u32>, MaxCertificates: Get<u32>> {
+    V1(PricePayload<MaxPrices, MaxCertificates>),
+    V2(PricePayloadV2<MaxPrices, MaxCertificates>),
+}
+
+impl<MaxPrices: Get<u32>, MaxCertificates: Get<u32>>
+    VersionedPricePayload<MaxPrices, MaxCertificates>
+{
+    /// Decodes a V2 payload if `data` starts with [`PRICE_PAYLOAD_V2_MAGIC`], a V1 payload otherwise.
+    pub fn decode_from(data: &[u8]) -> Result<Self, scale::Error> {
+        match data.strip_prefix(PRICE_PAYLOAD_V2_MAGIC.as_slice()) {
+            Some(mut payload) => PricePayloadV2::decode(&mut payload).map(Self::V2),
+            None => PricePayload::decode(&mut &data[..]).map(Self::V1),
+        }
+    }
+
+    /// Encodes the payload, as read by [`Self::decode_from`].
+    pub fn encode(&self) -> Vec<u8> {
+        match self {
+            Self::V1(payload) => payload.encode(),
+            Self::V2(payload) => {
+                let mut data = PRICE_PAYLOAD_V2_MAGIC.to_vec();
+                payload.encode_to(&mut data);
+                data
+            }
+        }
+    }
+
+    /// The maximum length of an encoded payload of any version.
+    pub fn max_encoded_len() -> usize {
+        PricePayload::<MaxPrices, MaxCertificates>::max_encoded_len().max(
+            PRICE_PAYLOAD_V2_MAGIC.len()
+                + PricePayloadV2::<MaxPrices, MaxCertificates>::max_encoded_len(),
+        )
+    }
+
+    /// Returns true if the decimals and the confidences, if any, match the prices.
+    pub fn is_consistent(&self) -> bool {
+        match self {
+            Self::V1(_) => true,
+            Self::V2(payload) => {
+                payload.decimals.len() == payload.prices.len()
+                    && payload.confidences.len() == payload.prices.len()
+            }
+        }
+    }
+
+    pub fn request_hash(&self) -> RequestHash {
+        match self {
+            Self::V1(payload) => payload.request_hash,
+            Self::V2(payload) => payload.request_hash,
+        }
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            Self::V1(payload) => payload.timestamp,
+            Self::V2(payload) => payload.timestamp,
+        }
+    }
+
+    pub fn prices(&self) -> &BoundedVec<u128, MaxPrices> {
+        match self {
+            Self::V1(payload) => &payload.prices,
+            Self::V2(payload) => &payload.prices,
+        }
+    }
+
+    pub fn certificates(&self) -> &BoundedVec<Certificate, MaxCertificates> {
+        match self {
+            Self::V1(payload) => &payload.certificates,
+            Self::V2(payload) => &payload.certificates,
+        }
+    }
+
+    pub fn valid_until(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.valid_until),
+        }
+    }
+
+    pub fn sequence(&self) -> Option<u64> {
+        match self {
+            Self::V1(_) => None,
+            Self::V2(payload) => Some(payload.sequence),
+        }
+    }
+
+    /// The price entry stored for the payload.
+    pub fn into_price_entry(self) -> PriceEntry<MaxPrices> {
+        match self {
+            Self::V1(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                decimals: Default::default(),
+                confidences: Default::default(),
+                valid_until: None,
+                sequence: None,
+            },
+            Self::V2(payload) => PriceEntry {
+                timestamp: payload.timestamp,
+                prices: payload.prices,
+                decimals: payload.decimals,
+                confidences: payload.confidences,
+                valid_until: Some(payload.valid_until),
+                sequence: Some(payload.sequence),
+            },
+        }
+    }
+}
+
+/// What happens to an update whose price deviates too much from the stored price of its feed.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum DeviationMode {
+    /// The update is skipped.
+    Reject,
+    /// The update is stored, but the feed is suspended until an admin resumes it.
+    Suspend,
+}
+
+/// The maximum deviation allowed between two consecutive prices of a feed.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub struct DeviationGuard {
+    /// The maximum deviation of any price of the feed, in basis points of the stored price.
+    pub max_deviation_bps: u32,
+    pub mode: DeviationMode,
+}
+
+/// Overrides of the global oracle configuration for a single feed, `None` falls back to the global value.
+#[derive(
+    RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq, Default,
+)]
+pub struct FeedConfig {
+    pub valid_signers_threshold: Option<u8>,
+    pub valid_sources_threshold: Option<u8>,
+    /// In milliseconds.
+    pub valid_time_period: Option<u64>,
+}
+
+/// The description of a registered feed, needed to interpret its prices.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FeedMetadata<MaxSymbolLen: Get<u32>, MaxDescriptionLen: Get<u32>, MaxPrices: Get<u32>> {
+    /// A human-readable symbol, e.g. `BTC/USD`.
+    pub symbol: BoundedVec<u8, MaxSymbolLen>,
+    pub description: BoundedVec<u8, MaxDescriptionLen>,
+    /// The number of decimals of the price at each index.
+    pub decimals: BoundedVec<u8, MaxPrices>,
+    /// The label of the price at each index, e.g. `bid`, `ask` and `mid`.
+    pub labels: BoundedVec<BoundedVec<u8, MaxSymbolLen>, MaxPrices>,
+}
+
+impl<MaxSymbolLen: Get<u32>, MaxDescriptionLen: Get<u32>, MaxPrices: Get<u32>>
+    FeedMetadata<MaxSymbolLen, MaxDescriptionLen, MaxPrices>
+{
+    /// Returns true if there is a label for the price at each index with decimals.
+    pub fn is_consistent(&self) -> bool {
+        self.decimals.len() == self.labels.len()
+    }
+}
+
+/// The unbounded counterpart of [`FeedMetadata`] returned by the runtime API, both have the same encoding.
+#[derive(RuntimeDebug, Encode, Decode, TypeInfo, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct FeedInfo {
+    pub symbol: Vec<u8>,
+    pub description: Vec<u8>,
+    pub decimals: Vec<u8>,
+    pub labels: Vec<Vec<u8>>,
+}
+
+impl<MaxSymbolLen: Get<u32>, MaxDescriptionLen: Get<u32>, MaxPrices: Get<u32>>
+    From<FeedMetadata<MaxSymbolLen, MaxDescriptionLen, MaxPrices>> for FeedInfo
+{
+    fn from(metadata: FeedMetadata<MaxSymbolLen, MaxDescriptionLen, MaxPrices>) -> Self {
+        Self {
+            symbol: metadata.symbol.into_inner(),
+            description: metadata.description.into_inner(),
+            decimals: metadata.decimals.into_inner(),
+            labels: metadata
+                .labels
+                .into_iter()
+                .map(BoundedVec::into_inner)
+                .collect(),
+        }
+    }
+}
+
+/// The reason a single price update was rejected.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateRejectionReason {
+    NotEnoughValidSignatures,
+    InvalidPayload,
+    NotEnoughValidSources,
+    TimestampInFuture,
+    TimestampTooOld,
+    /// The registered feeds only mode is set and the feed is not in the registry.
+    FeedNotRegistered,
+    /// The payload has expired.
+    PayloadExpired,
+    /// The data is bound to another deployment, or is a legacy payload while a signing domain is required.
+    InvalidSigningDomain,
+}
+
+/// The outcome of a single price update of a batch.
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum PriceUpdateResult {
+    /// The price feed was updated.
+    Updated,
+    /// The update was valid, but the stored price is at least as recent.
+    NotMoreRecent,
+    /// The update was valid, but its price deviates too much from the stored one.
+    DeviationRejected,
+    /// The update was rejected.
+    Rejected(UpdateRejectionReason),
+}
+
+/// The work performed while processing a batch of price updates, used to compute its actual weight.
+#[derive(Default)]
+pub(crate) struct UpdateCounters {
+    /// Updates that were processed.
+    pub updates: u32,
+    /// Verified updates that were skipped as not more recent, before any write.
+    pub skipped: u32,
+    /// The most signatures recovered for a single update.
+    pub signatures: u32,
+    /// The most certificates looked up in the trust store for a single update.
+    pub certificates: u32,
+}
+
+impl UpdateCounters {
+    /// Counts the path a verified update took when its price was stored.
+    pub fn record(&mut self, result: PriceUpdateResult) {
+        if result == PriceUpdateResult::NotMoreRecent {
+            self.skipped += 1;
+        }
+    }
+}
+
+/// The reason a price could not be provided, see [`crate::traits::PriceProvider`].
+#[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum PriceError {
+    /// No price was ever set for the feed.
+    NotFound,
+    /// The price is older than the requested age.
+    Stale,
+    /// The feed has no price at the requested index.
+    IndexOutOfRange,
+    /// The feed has not been observed for the whole requested window.
+    InsufficientHistory,
+    /// The feed is suspended after a price deviation, until an admin resumes it.
+    Suspended,
+}
+
 #[derive(RuntimeDebug, Encode, Decode, MaxEncodedLen, TypeInfo, Clone, PartialEq, Eq)]
 pub struct Signature([u8; 65]);
 
@@ -133,6 +502,23 @@ pub type CertificateTrustStoreUpdate = ListUpdate<Certificate>;
 pub type MaxPricesFor<T> = <T as crate::Config>::MaxPrices;
 pub type MaxCertificatesFor<T> = <T as crate::Config>::MaxCertificates;
 pub type MaxPriceUpdatesFor<T> = <T as crate::Config>::MaxPriceUpdates;
+pub type MaxSignaturesPerUpdateFor<T> = <T as crate::Config>::MaxSignaturesPerUpdate;
+pub type MaxPayloadLenFor<T> = <T as crate::Config>::MaxPayloadLen;
+pub type MaxHistoryFor<T> = <T as crate::Config>::MaxHistory;
+pub type MaxSymbolLenFor<T> = <T as crate::Config>::MaxSymbolLen;
+pub type MaxDescriptionLenFor<T> = <T as crate::Config>::MaxDescriptionLen;
 
 pub type PriceEntryFor<T> = PriceEntry<MaxPricesFor<T>>;
+pub type PriceHistoryEntryFor<T> = PriceHistoryEntry<MaxPricesFor<T>, BlockNumberFor<T>>;
+pub type PriceObservationFor<T> = PriceObservation<MaxPricesFor<T>>;
+pub type FeedMetadataFor<T> =
+    FeedMetadata<MaxSymbolLenFor<T>, MaxDescriptionLenFor<T>, MaxPricesFor<T>>;
 pub type PricePayloadFor<T> = PricePayload<MaxPricesFor<T>, MaxCertificatesFor<T>>;
+pub type PricePayloadV2For<T> = PricePayloadV2<MaxPricesFor<T>, MaxCertificatesFor<T>>;
+pub type VersionedPricePayloadFor<T> =
+    VersionedPricePayload<MaxPricesFor<T>, MaxCertificatesFor<T>>;
+/// The SCALE encoded [`PricePayload`] of a single price update.
+pub type UpdateDataFor<T> = BoundedVec<u8, MaxPayloadLenFor<T>>;
+/// The oracle signatures of a single price update.
+pub type UpdateSignaturesFor<T> =
+    BoundedVec<<T as crate::Config>::Signature, MaxSignaturesPerUpdateFor<T>>;
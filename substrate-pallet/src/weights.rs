@@ -0,0 +1,77 @@
+//! Placeholder weights for `pallet_acelon_oracle`.
+//!
+//! THESE WEIGHTS ARE NOT MEASURED. They are flat constants: every call costs
+//! [`PLACEHOLDER_WEIGHT`], once per update for the update extrinsics, whatever its signatures,
+//! certificates and storage accesses. They only let the pallet be used in tests and development
+//! chains. Runtimes must generate their weights from the benchmarks in `benchmarking.rs` on their
+//! reference hardware before deploying, see the README.
+
+use frame_support::weights::Weight;
+
+use crate::traits::WeightInfo;
+
+/// The placeholder weight of a call, or of a single update of a batch.
+pub const PLACEHOLDER_WEIGHT: Weight = Weight::from_parts(10_000, 0);
+
+impl WeightInfo for () {
+    fn update_price_feeds(updates: u32, _signatures: u32, _certificates: u32) -> Weight {
+        PLACEHOLDER_WEIGHT.saturating_mul(updates.into())
+    }
+    fn try_update_price_feeds(updates: u32, _signatures: u32, _certificates: u32) -> Weight {
+        PLACEHOLDER_WEIGHT.saturating_mul(updates.into())
+    }
+    fn submit_price_feeds_unsigned(updates: u32, _signatures: u32, _certificates: u32) -> Weight {
+        PLACEHOLDER_WEIGHT.saturating_mul(updates.into())
+    }
+    fn set_price(_written: u32) -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_signers_threshold() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_sources_threshold() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_valid_time_period() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_max_future_drift() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_max_submission_age() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_registered_feeds_only() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_require_signing_domain() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_trusted_signer() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_certificate_trust_store() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn set_deviation_guard() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn resume_price_feed() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn set_feed_config() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn clear_feed_config() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn register_feed() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn update_feed_metadata() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+    fn deregister_feed() -> Weight {
+        PLACEHOLDER_WEIGHT
+    }
+}